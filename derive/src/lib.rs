@@ -0,0 +1,78 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, LitInt, LitStr};
+
+/// Generates `impl From<YourError> for AppError` from per-variant
+/// `#[app_error(status = 404, code = "USER_NOT_FOUND")]` attributes. The
+/// variant's `Display` output becomes the message.
+#[proc_macro_derive(IntoAppError, attributes(app_error))]
+pub fn derive_into_app_error(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let Data::Enum(data) = &input.data else {
+        return Err(Error::new_spanned(
+            name,
+            "IntoAppError can only be derived for enums",
+        ));
+    };
+
+    let mut arms = Vec::new();
+    for variant in &data.variants {
+        let (status, code) = variant_attributes(variant)?;
+        let ident = &variant.ident;
+        arms.push(quote! {
+            #name::#ident { .. } => (#status, #code),
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#name #ty_generics>
+            for ::error_util::error::AppError #where_clause
+        {
+            fn from(err: #name #ty_generics) -> Self {
+                let (status, code): (u16, &str) = match &err {
+                    #(#arms)*
+                };
+                ::error_util::error::AppError::custom(status, code, err.to_string())
+            }
+        }
+    })
+}
+
+fn variant_attributes(variant: &syn::Variant) -> syn::Result<(u16, String)> {
+    let mut status = None;
+    let mut code = None;
+
+    for attr in variant
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("app_error"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("status") {
+                status = Some(meta.value()?.parse::<LitInt>()?.base10_parse::<u16>()?);
+                Ok(())
+            } else if meta.path.is_ident("code") {
+                code = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("expected `status` or `code`"))
+            }
+        })?;
+    }
+
+    match (status, code) {
+        (Some(status), Some(code)) => Ok((status, code)),
+        _ => Err(Error::new_spanned(
+            &variant.ident,
+            "missing #[app_error(status = ..., code = \"...\")]",
+        )),
+    }
+}
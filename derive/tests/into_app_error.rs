@@ -0,0 +1,35 @@
+use std::fmt;
+
+use actix_web::ResponseError;
+use error_util::error::{AppError, IntoAppError};
+
+#[derive(Debug, IntoAppError)]
+enum UserError {
+    #[app_error(status = 404, code = "USER_NOT_FOUND")]
+    NotFound(u64),
+    #[app_error(status = 409, code = "EMAIL_TAKEN")]
+    EmailTaken { email: String },
+}
+
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserError::NotFound(id) => write!(f, "user {} not found", id),
+            UserError::EmailTaken { email } => write!(f, "{} is already registered", email),
+        }
+    }
+}
+
+#[test]
+fn variants_map_to_their_configured_status_and_code() {
+    let err = AppError::from(UserError::NotFound(42));
+    assert_eq!(err.status_code().as_u16(), 404);
+    assert_eq!(err.code(), "USER_NOT_FOUND");
+    assert_eq!(err.to_string(), "USER_NOT_FOUND: user 42 not found");
+
+    let err = AppError::from(UserError::EmailTaken {
+        email: "a@example.com".into(),
+    });
+    assert_eq!(err.status_code().as_u16(), 409);
+    assert_eq!(err.code(), "EMAIL_TAKEN");
+}
@@ -0,0 +1,10 @@
+use error_util::error::IntoAppError;
+
+#[derive(Debug, IntoAppError)]
+enum UserError {
+    #[app_error(status = 404, code = "USER_NOT_FOUND")]
+    NotFound,
+    Locked,
+}
+
+fn main() {}
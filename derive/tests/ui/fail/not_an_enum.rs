@@ -0,0 +1,8 @@
+use error_util::error::IntoAppError;
+
+#[derive(Debug, IntoAppError)]
+struct UserError {
+    id: u64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use error_util::error::IntoAppError;
+
+#[derive(Debug, IntoAppError)]
+enum UserError {
+    #[app_error(status = 404, kind = "USER_NOT_FOUND")]
+    NotFound,
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+use std::fmt;
+
+use error_util::error::IntoAppError;
+
+#[derive(Debug, IntoAppError)]
+enum UserError {
+    #[app_error(status = 404, code = "USER_NOT_FOUND")]
+    NotFound(u64),
+    #[app_error(status = 409, code = "EMAIL_TAKEN")]
+    EmailTaken { email: String },
+    #[app_error(status = 503, code = "DIRECTORY_DOWN")]
+    DirectoryDown,
+}
+
+impl fmt::Display for UserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+fn main() {
+    let _ = error_util::error::AppError::from(UserError::NotFound(42));
+}
@@ -0,0 +1,72 @@
+use std::io::ErrorKind;
+
+use zip::result::ZipError;
+
+use super::{AppError, HttpError};
+
+impl From<ZipError> for AppError {
+    fn from(err: ZipError) -> Self {
+        match err {
+            // the upload was cut short before the central directory
+            ZipError::Io(ref io) if io.kind() == ErrorKind::UnexpectedEof => {
+                AppError::BadRequest(format!("truncated archive: {}", err))
+            }
+            ZipError::Io(_) => AppError::ServerError(HttpError {
+                status: 500,
+                message: format!("archive error: {}", err),
+            }),
+            // the end-of-central-directory record lives at the tail, so these
+            // mean the upload stopped short rather than that it is malformed
+            ZipError::InvalidArchive(
+                "Could not find EOCD"
+                | "Could not find EOCD64"
+                | "Invalid central directory size or offset",
+            ) => AppError::BadRequest(format!("truncated archive: {}", err)),
+            ZipError::InvalidPassword => AppError::BadRequest(format!("archive error: {}", err)),
+            ZipError::FileNotFound => {
+                AppError::UnprocessableEntity("archive entry not found".into())
+            }
+            _ => AppError::UnprocessableEntity(format!("corrupt archive: {}", err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use zip::write::SimpleFileOptions;
+    use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+    use super::*;
+
+    fn archive() -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        writer.start_file("report.csv", options).unwrap();
+        writer.write_all(b"id,total\n1,42\n").unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn truncated_archive_is_a_bad_request() {
+        let bytes = archive();
+        let truncated = &bytes[..bytes.len() / 2];
+        let err = AppError::from(ZipArchive::new(Cursor::new(truncated)).unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert!(err.to_string().contains("truncated archive"));
+    }
+
+    #[test]
+    fn corrupt_archive_is_unprocessable() {
+        let mut bytes = archive();
+        // break the central directory header signature, leaving the tail intact
+        let header = bytes.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+        bytes[header + 2] = 0;
+        let err = AppError::from(ZipArchive::new(Cursor::new(bytes)).unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(err.to_string().contains("corrupt archive"));
+    }
+}
@@ -0,0 +1,61 @@
+use biscuit::errors::{Error as BiscuitError, ValidationError as ClaimsError};
+
+use super::{AppError, HttpError};
+
+impl From<BiscuitError> for AppError {
+    fn from(err: BiscuitError) -> Self {
+        match &err {
+            BiscuitError::ValidationError(ClaimsError::Expired(_) | ClaimsError::TooOld(_)) => {
+                AppError::custom(401, "TOKEN_EXPIRED", "token has expired")
+            }
+            BiscuitError::ValidationError(ClaimsError::InvalidSignature) => {
+                AppError::custom(401, "SIGNATURE_INVALID", "invalid signature")
+            }
+            BiscuitError::ValidationError(_) => AppError::Unauthorized,
+            BiscuitError::DecodeError(_)
+            | BiscuitError::DecodeBase64(_)
+            | BiscuitError::JsonError(_)
+            | BiscuitError::Utf8(_) => AppError::BadRequest(format!("malformed token: {}", err)),
+            // our own keys or options are unusable
+            _ => AppError::ServerError(HttpError {
+                status: 500,
+                message: format!("jwt key error: {}", err),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use biscuit::jwa::SignatureAlgorithm;
+    use biscuit::jws::Secret;
+    use biscuit::{Empty, RegisteredClaims, ValidationOptions, JWT};
+
+    use super::*;
+
+    #[test]
+    fn expired_token_is_a_401() {
+        let claims = RegisteredClaims {
+            expiry: Some(1_000_000_000.into()),
+            ..Default::default()
+        };
+        let err = claims.validate(ValidationOptions::default()).unwrap_err();
+        let err = AppError::from(BiscuitError::from(err));
+        assert_eq!(err.status_code(), StatusCode::UNAUTHORIZED);
+        assert_eq!(err.code(), "TOKEN_EXPIRED");
+    }
+
+    #[test]
+    fn malformed_token_is_a_bad_request() {
+        let token = JWT::<Empty, Empty>::new_encoded("not-a-token");
+        let err = token
+            .into_decoded(
+                &Secret::Bytes(b"secret".to_vec()),
+                SignatureAlgorithm::HS256,
+            )
+            .unwrap_err();
+        assert_eq!(AppError::from(err).status_code(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,263 @@
+use std::time::Duration;
+
+use actix_web::http::StatusCode;
+
+use super::AppError;
+
+/// The common kinds of error a handler reports, for
+/// [`AppErrorBuilder::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict,
+    UnprocessableEntity,
+    RateLimited,
+    Internal,
+    ServiceUnavailable,
+    Timeout,
+}
+
+impl ErrorKind {
+    pub fn status(self) -> StatusCode {
+        match self {
+            ErrorKind::BadRequest => StatusCode::BAD_REQUEST,
+            ErrorKind::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorKind::Forbidden => StatusCode::FORBIDDEN,
+            ErrorKind::NotFound => StatusCode::NOT_FOUND,
+            ErrorKind::Conflict => StatusCode::CONFLICT,
+            ErrorKind::UnprocessableEntity => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorKind::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorKind::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorKind::ServiceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ErrorKind::Timeout => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+
+    /// The dedicated variant for this kind, where there is one.
+    fn variant(self, message: String, retry_after: Option<Duration>) -> Option<AppError> {
+        match self {
+            ErrorKind::BadRequest => Some(AppError::BadRequest(message)),
+            ErrorKind::NotFound => Some(AppError::NotFound(message)),
+            ErrorKind::UnprocessableEntity => Some(AppError::UnprocessableEntity(message)),
+            ErrorKind::ServiceUnavailable => Some(AppError::ServiceUnavailable(message)),
+            ErrorKind::Timeout => Some(AppError::Timeout(message)),
+            ErrorKind::Unauthorized if message.is_empty() => Some(AppError::Unauthorized),
+            ErrorKind::RateLimited if message.is_empty() => retry_after.map(AppError::RateLimited),
+            _ => None,
+        }
+    }
+}
+
+/// Fluent construction for handlers that gather an error's details in
+/// several steps, e.g.
+/// `AppError::builder().kind(ErrorKind::Conflict).code("DUP").field("email", "taken").build()`.
+#[derive(Debug, Clone)]
+pub struct AppErrorBuilder {
+    kind: Option<ErrorKind>,
+    status: Option<StatusCode>,
+    code: Option<String>,
+    message: String,
+    fields: Vec<(String, String)>,
+    details: Option<serde_json::Value>,
+    context: Vec<(String, String)>,
+    retry_after: Option<Duration>,
+    error_id: bool,
+}
+
+impl AppError {
+    pub fn builder() -> AppErrorBuilder {
+        AppErrorBuilder {
+            kind: None,
+            status: None,
+            code: None,
+            message: String::new(),
+            fields: Vec::new(),
+            details: None,
+            context: Vec::new(),
+            retry_after: None,
+            error_id: false,
+        }
+    }
+}
+
+impl AppErrorBuilder {
+    /// Picks the status and, unless a code or a different status is set,
+    /// the matching variant, e.g. [`AppError::NotFound`].
+    pub fn kind(mut self, kind: ErrorKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Overrides the kind's status. Defaults to the kind's, or to 500.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Defaults to the one derived from the status, e.g. `CONFLICT`.
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Listed under `errors` in the envelope; may be called repeatedly.
+    pub fn field(mut self, field: impl Into<String>, message: impl Into<String>) -> Self {
+        self.fields.push((field.into(), message.into()));
+        self
+    }
+
+    /// Same as [`AppError::with_details`] on the built error.
+    pub fn details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    /// Same as [`AppError::with_context`] on the built error; may be called
+    /// repeatedly.
+    pub fn context(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.context.push((key.into(), value.to_string()));
+        self
+    }
+
+    /// Adds a `Retry-After` header. The status and code set on the builder
+    /// are kept; with neither a kind nor a status the error is a 429.
+    pub fn retry_after(mut self, delay: Duration) -> Self {
+        self.retry_after = Some(delay);
+        self
+    }
+
+    /// Same as [`AppError::with_error_id`] on the built error.
+    pub fn error_id(mut self) -> Self {
+        self.error_id = true;
+        self
+    }
+
+    pub fn build(self) -> AppError {
+        // a bare retry hint reads as rate limiting
+        let kind = self
+            .kind
+            .or(self.retry_after.map(|_| ErrorKind::RateLimited));
+        let status = self
+            .status
+            .or(kind.map(ErrorKind::status))
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+        let variant = match (kind, &self.code) {
+            (Some(kind), None) if kind.status() == status => {
+                kind.variant(self.message.clone(), self.retry_after)
+            }
+            _ => None,
+        };
+        let mut error = variant.unwrap_or_else(|| {
+            AppError::custom(
+                status.as_u16(),
+                self.code
+                    .unwrap_or_else(|| AppError::code_for_status(status)),
+                self.message,
+            )
+        });
+
+        if let Some(delay) = self.retry_after {
+            if error.retry_after() != Some(delay) {
+                error = error.with_retry_after(delay);
+            }
+        }
+        for (field, message) in self.fields {
+            error = error.with_field(field, message);
+        }
+        if let Some(details) = self.details {
+            error = error.with_details(details);
+        }
+        for (key, value) in self.context {
+            error = error.with_context(key, value);
+        }
+        if self.error_id {
+            error = error.with_error_id();
+        }
+        error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::ResponseError;
+
+    use super::*;
+
+    #[test]
+    fn conflict_with_a_field() {
+        let err = AppError::builder()
+            .kind(ErrorKind::Conflict)
+            .message("already registered")
+            .code("DUP")
+            .field("email", "taken")
+            .build();
+
+        assert_eq!(err.status_code(), StatusCode::CONFLICT);
+        assert_eq!(err.code(), "DUP");
+        let envelope = serde_json::to_value(err.envelope()).unwrap();
+        assert_eq!(envelope["message"], "already registered");
+        assert_eq!(envelope["errors"][0]["field"], "email");
+        assert_eq!(envelope["errors"][0]["message"], "taken");
+    }
+
+    #[test]
+    fn rate_limited_with_a_retry_hint() {
+        let err = AppError::builder()
+            .kind(ErrorKind::RateLimited)
+            .retry_after(Duration::from_secs(30))
+            .build();
+
+        assert!(matches!(err, AppError::RateLimited(delay) if delay == Duration::from_secs(30)));
+        let response = ResponseError::error_response(&err);
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get("Retry-After").unwrap(), "30");
+    }
+
+    #[test]
+    fn retry_hint_keeps_the_explicit_status_and_code() {
+        let err = AppError::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .code("MAINTENANCE")
+            .message("back soon")
+            .retry_after(Duration::from_secs(120))
+            .build();
+
+        assert_eq!(err.code(), "MAINTENANCE");
+        let response = ResponseError::error_response(&err);
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers().get("Retry-After").unwrap(), "120");
+    }
+
+    #[test]
+    fn kind_picks_the_dedicated_variant() {
+        let err = AppError::builder()
+            .kind(ErrorKind::NotFound)
+            .message("user 7")
+            .build();
+        assert!(matches!(err, AppError::NotFound(ref msg) if msg == "user 7"));
+    }
+
+    #[test]
+    fn details_and_context() {
+        let err = AppError::builder()
+            .kind(ErrorKind::BadRequest)
+            .message("unsupported currency")
+            .details(serde_json::json!({ "supported": ["EUR", "USD"] }))
+            .context("order_id", 42)
+            .build();
+
+        let envelope = serde_json::to_value(err.envelope()).unwrap();
+        assert_eq!(envelope["details"]["supported"][1], "USD");
+        assert!(!envelope.to_string().contains("order_id"));
+        assert_eq!(err.context(), vec![("order_id".into(), "42".into())]);
+    }
+}
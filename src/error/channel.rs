@@ -0,0 +1,199 @@
+use super::{AppError, HttpError};
+
+fn closed() -> AppError {
+    AppError::ServiceUnavailable("channel closed: the worker has shut down".into())
+}
+
+fn failed(reason: &str) -> AppError {
+    AppError::ServerError(HttpError {
+        status: 500,
+        message: format!("channel error: {}", reason),
+    })
+}
+
+#[cfg(feature = "flume")]
+mod flume_impl {
+    use flume::{RecvError, SendError, TryRecvError, TrySendError};
+
+    use super::{closed, failed, AppError};
+
+    impl<T> From<SendError<T>> for AppError {
+        fn from(_: SendError<T>) -> Self {
+            closed()
+        }
+    }
+
+    impl<T> From<TrySendError<T>> for AppError {
+        fn from(err: TrySendError<T>) -> Self {
+            match err {
+                TrySendError::Disconnected(_) => closed(),
+                TrySendError::Full(_) => failed("channel is full"),
+            }
+        }
+    }
+
+    impl From<RecvError> for AppError {
+        fn from(_: RecvError) -> Self {
+            closed()
+        }
+    }
+
+    impl From<TryRecvError> for AppError {
+        fn from(err: TryRecvError) -> Self {
+            match err {
+                TryRecvError::Disconnected => closed(),
+                TryRecvError::Empty => failed("channel is empty"),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "crossbeam-channel")]
+mod crossbeam_impl {
+    use crossbeam_channel::{RecvError, SendError, TryRecvError, TrySendError};
+
+    use super::{closed, failed, AppError};
+
+    impl<T> From<SendError<T>> for AppError {
+        fn from(_: SendError<T>) -> Self {
+            closed()
+        }
+    }
+
+    impl<T> From<TrySendError<T>> for AppError {
+        fn from(err: TrySendError<T>) -> Self {
+            match err {
+                TrySendError::Disconnected(_) => closed(),
+                TrySendError::Full(_) => failed("channel is full"),
+            }
+        }
+    }
+
+    impl From<RecvError> for AppError {
+        fn from(_: RecvError) -> Self {
+            closed()
+        }
+    }
+
+    impl From<TryRecvError> for AppError {
+        fn from(err: TryRecvError) -> Self {
+            match err {
+                TryRecvError::Disconnected => closed(),
+                TryRecvError::Empty => failed("channel is empty"),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod tokio_impl {
+    use tokio::sync::{mpsc, oneshot};
+
+    use super::{closed, failed, AppError};
+
+    impl From<oneshot::error::RecvError> for AppError {
+        fn from(_: oneshot::error::RecvError) -> Self {
+            closed()
+        }
+    }
+
+    impl From<oneshot::error::TryRecvError> for AppError {
+        fn from(err: oneshot::error::TryRecvError) -> Self {
+            match err {
+                oneshot::error::TryRecvError::Closed => closed(),
+                oneshot::error::TryRecvError::Empty => failed("channel is empty"),
+            }
+        }
+    }
+
+    impl<T> From<mpsc::error::SendError<T>> for AppError {
+        fn from(_: mpsc::error::SendError<T>) -> Self {
+            closed()
+        }
+    }
+
+    impl<T> From<mpsc::error::TrySendError<T>> for AppError {
+        fn from(err: mpsc::error::TrySendError<T>) -> Self {
+            match err {
+                mpsc::error::TrySendError::Closed(_) => closed(),
+                mpsc::error::TrySendError::Full(_) => failed("channel is full"),
+            }
+        }
+    }
+
+    impl From<mpsc::error::TryRecvError> for AppError {
+        fn from(err: mpsc::error::TryRecvError) -> Self {
+            match err {
+                mpsc::error::TryRecvError::Disconnected => closed(),
+                mpsc::error::TryRecvError::Empty => failed("channel is empty"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+
+    use super::AppError;
+
+    #[cfg(feature = "flume")]
+    #[test]
+    fn flume_send_on_closed_channel_is_a_503() {
+        let (tx, rx) = flume::unbounded::<u8>();
+        drop(rx);
+        let err = AppError::from(tx.send(1).unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[cfg(feature = "flume")]
+    #[test]
+    fn flume_recv_errors() {
+        let (tx, rx) = flume::unbounded::<u8>();
+        let err = AppError::from(rx.try_recv().unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        drop(tx);
+        let err = AppError::from(rx.recv().unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[cfg(feature = "crossbeam-channel")]
+    #[test]
+    fn crossbeam_send_on_closed_channel_is_a_503() {
+        let (tx, rx) = crossbeam_channel::unbounded::<u8>();
+        drop(rx);
+        let err = AppError::from(tx.send(1).unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[cfg(feature = "crossbeam-channel")]
+    #[test]
+    fn crossbeam_recv_errors() {
+        let (tx, rx) = crossbeam_channel::unbounded::<u8>();
+        let err = AppError::from(rx.try_recv().unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        drop(tx);
+        let err = AppError::from(rx.recv().unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn tokio_recv_after_the_worker_dropped_its_sender_is_a_503() {
+        use tokio::sync::{mpsc, oneshot};
+
+        let (tx, rx) = oneshot::channel::<u8>();
+        drop(tx);
+        let err = AppError::from(rx.blocking_recv().unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(err.to_string().contains("worker"), "{}", err);
+
+        let (tx, mut rx) = mpsc::channel::<u8>(1);
+        drop(tx);
+        let err = AppError::from(rx.try_recv().unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,30 @@
+use clap::Error as ClapError;
+
+use super::AppError;
+
+impl From<ClapError> for AppError {
+    fn from(err: ClapError) -> Self {
+        // clap's rendering already includes the usage line
+        AppError::BadRequest(err.render().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use clap::{Arg, Command};
+
+    use super::*;
+
+    #[test]
+    fn parse_error_is_a_bad_request_with_usage() {
+        let err = Command::new("admin")
+            .arg(Arg::new("tenant").long("tenant").required(true))
+            .try_get_matches_from(["admin"])
+            .unwrap_err();
+        let err = AppError::from(err);
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert!(err.to_string().contains("Usage: admin"), "{}", err);
+    }
+}
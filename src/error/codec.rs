@@ -0,0 +1,47 @@
+use bincode::error::{DecodeError, EncodeError};
+
+use super::{AppError, HttpError};
+
+impl From<EncodeError> for AppError {
+    fn from(err: EncodeError) -> Self {
+        AppError::ServerError(HttpError {
+            status: 500,
+            message: format!("encode error: {}", err),
+        })
+    }
+}
+
+impl From<DecodeError> for AppError {
+    fn from(err: DecodeError) -> Self {
+        AppError::UnprocessableEntity(format!("decode error: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+
+    use super::*;
+
+    #[test]
+    fn encode_error_is_a_500() {
+        let mut buffer = [0u8; 2];
+        let err = bincode::encode_into_slice("too long", &mut buffer, bincode::config::standard())
+            .unwrap_err();
+        assert_eq!(
+            AppError::from(err).status_code(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn decode_error_is_a_422() {
+        let err = bincode::decode_from_slice::<String, _>(&[5, b'a'], bincode::config::standard())
+            .unwrap_err();
+        assert_eq!(
+            AppError::from(err).status_code(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+}
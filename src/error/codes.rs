@@ -0,0 +1,50 @@
+use std::collections::HashSet;
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+use super::AppError;
+
+#[derive(Default)]
+struct CodePolicy {
+    strict: bool,
+    registered: HashSet<String>,
+}
+
+fn policy() -> &'static RwLock<CodePolicy> {
+    static POLICY: OnceLock<RwLock<CodePolicy>> = OnceLock::new();
+    POLICY.get_or_init(Default::default)
+}
+
+impl AppError {
+    /// For strict API contracts: once enabled, any `code` not registered
+    /// through [`AppError::register_codes`] reaches clients as `UNKNOWN`.
+    /// Log events still carry the error's `kind`.
+    pub fn set_strict_codes(strict: bool) {
+        policy()
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .strict = strict;
+    }
+
+    /// Adds to the codes allowed through under [`AppError::set_strict_codes`].
+    pub fn register_codes<I, S>(codes: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        policy()
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .registered
+            .extend(codes.into_iter().map(Into::into));
+    }
+}
+
+/// `code` itself, or `UNKNOWN` if strict mode is on and it is unregistered.
+pub(super) fn apply_policy(code: String) -> String {
+    let policy = policy().read().unwrap_or_else(PoisonError::into_inner);
+    if policy.strict && !policy.registered.contains(&code) {
+        "UNKNOWN".into()
+    } else {
+        code
+    }
+}
@@ -0,0 +1,52 @@
+use actix_web::http::StatusCode;
+use rusqlite::{Error as DatabaseError, ErrorCode};
+
+use super::AppError;
+
+pub(super) fn status_code(err: &DatabaseError) -> StatusCode {
+    match err {
+        DatabaseError::QueryReturnedNoRows => StatusCode::NOT_FOUND,
+        _ if err.sqlite_error_code() == Some(ErrorCode::ConstraintViolation) => {
+            StatusCode::CONFLICT
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl From<DatabaseError> for AppError {
+    fn from(err: DatabaseError) -> Self {
+        AppError::Database(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::ResponseError;
+    use rusqlite::Connection;
+
+    use super::*;
+
+    #[test]
+    fn no_rows_is_a_404() {
+        let conn = Connection::open_in_memory().unwrap();
+        let err = conn
+            .query_row("SELECT 1 WHERE 0", [], |row| row.get::<_, i64>(0))
+            .unwrap_err();
+        assert_eq!(AppError::from(err).status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn constraint_violation_is_a_409() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute("CREATE TABLE users (email TEXT UNIQUE)", [])
+            .unwrap();
+        conn.execute("INSERT INTO users VALUES ('a@example.com')", [])
+            .unwrap();
+        let err = conn
+            .execute("INSERT INTO users VALUES ('a@example.com')", [])
+            .unwrap_err();
+        let err = AppError::from(err);
+        assert_eq!(err.status_code(), StatusCode::CONFLICT);
+        assert!(err.to_string().starts_with("database error: "));
+    }
+}
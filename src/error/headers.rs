@@ -0,0 +1,92 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::sync::{PoisonError, RwLock};
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderMap, HeaderName};
+
+use super::AppError;
+
+static STRIPPED: RwLock<Vec<HeaderName>> = RwLock::new(Vec::new());
+
+impl AppError {
+    /// Headers that must never leave on an error response, such as internal
+    /// auth or routing headers picked up while proxying. Names that are not
+    /// valid header names are ignored.
+    ///
+    /// Only the responses built from an `AppError` are covered on their own;
+    /// wrap the app in [`StripHeaders`] to cover every error response.
+    pub fn set_stripped_headers<I, S>(headers: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        *STRIPPED.write().unwrap_or_else(PoisonError::into_inner) = headers
+            .into_iter()
+            .filter_map(|name| HeaderName::try_from(name.as_ref()).ok())
+            .collect();
+    }
+}
+
+pub(super) fn strip(headers: &mut HeaderMap) {
+    for name in STRIPPED
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+    {
+        headers.remove(name);
+    }
+}
+
+/// Middleware removing the [stripped headers](AppError::set_stripped_headers)
+/// from every 4xx and 5xx response, including those a handler or another
+/// middleware builds itself. Register it last so it wraps the others, e.g.
+/// `App::new().wrap(proxy).wrap(StripHeaders)`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StripHeaders;
+
+impl<S, B> Transform<S, ServiceRequest> for StripHeaders
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = StripHeadersMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(StripHeadersMiddleware { service }))
+    }
+}
+
+/// The service [`StripHeaders`] wraps around the app.
+#[derive(Debug)]
+pub struct StripHeadersMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for StripHeadersMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let response = self.service.call(request);
+        Box::pin(async move {
+            let mut response = response.await?;
+            let status = response.status();
+            if status.is_client_error() || status.is_server_error() {
+                strip(response.headers_mut());
+            }
+            Ok(response)
+        })
+    }
+}
@@ -0,0 +1,28 @@
+use std::collections::HashMap;
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+use super::AppError;
+
+fn help_urls() -> &'static RwLock<HashMap<String, String>> {
+    static HELP_URLS: OnceLock<RwLock<HashMap<String, String>>> = OnceLock::new();
+    HELP_URLS.get_or_init(Default::default)
+}
+
+impl AppError {
+    /// Registers a documentation link, shown as `help_url` in the envelope of
+    /// every error with this `code`. Meant to be called once at startup.
+    pub fn set_help_url(code: impl Into<String>, url: impl Into<String>) {
+        help_urls()
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(code.into(), url.into());
+    }
+
+    pub(crate) fn help_url(&self) -> Option<String> {
+        help_urls()
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&self.code())
+            .cloned()
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::{PoisonError, RwLock};
+
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::http::header::HeaderMap;
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use actix_web::HttpResponse;
+
+use super::AppError;
+
+/// The error response as a [`PreResponseHook`] sees it, before it is sent.
+#[derive(Debug)]
+pub struct ResponseParts {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// The serialized envelope.
+    pub body: Bytes,
+}
+
+/// Called with every error response just before it is sent, e.g. to feed
+/// an audit sink or add a header behind a feature flag.
+pub type PreResponseHook = Box<dyn Fn(&AppError, &mut ResponseParts) + Send + Sync>;
+
+static HOOK: RwLock<Option<PreResponseHook>> = RwLock::new(None);
+
+impl AppError {
+    /// Installs the hook run by `error_response`, replacing any previous one.
+    /// Headers configured through [`AppError::set_stripped_headers`] are
+    /// removed after it runs, so it cannot add them back.
+    pub fn set_pre_response_hook(hook: PreResponseHook) {
+        *HOOK.write().unwrap_or_else(PoisonError::into_inner) = Some(hook);
+    }
+}
+
+pub(super) fn apply(error: &AppError, response: HttpResponse) -> HttpResponse {
+    let hook = HOOK.read().unwrap_or_else(PoisonError::into_inner);
+    let Some(hook) = hook.as_ref() else {
+        return response;
+    };
+
+    let (mut head, body) = response.into_parts();
+    let mut parts = ResponseParts {
+        status: head.status(),
+        headers: head.headers().clone(),
+        body: body.try_into_bytes().unwrap_or_default(),
+    };
+    hook(error, &mut parts);
+
+    *head.status_mut() = parts.status;
+    *head.headers_mut() = parts.headers;
+    head.set_body(BoxBody::new(parts.body))
+}
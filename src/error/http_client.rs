@@ -0,0 +1,58 @@
+use actix_web::http::StatusCode;
+use hyper::Error as HyperError;
+
+use super::AppError;
+
+pub(super) fn status_code(err: &HyperError) -> StatusCode {
+    if err.is_parse() {
+        StatusCode::BAD_REQUEST
+    } else if err.is_timeout() {
+        StatusCode::GATEWAY_TIMEOUT
+    } else {
+        // the peer went away or sent a truncated message
+        StatusCode::BAD_GATEWAY
+    }
+}
+
+impl From<HyperError> for AppError {
+    fn from(err: HyperError) -> Self {
+        AppError::Hyper(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::ResponseError;
+    use hyper::client::conn;
+    use hyper::{Body, Request};
+    use tokio::io::{duplex, AsyncWriteExt};
+
+    use super::*;
+
+    /// Sends a request to a fake server that answers with `reply` and hangs up.
+    async fn request_against(reply: &'static [u8]) -> HyperError {
+        let (client, mut server) = duplex(1024);
+        let (mut sender, connection) = conn::handshake(client).await.unwrap();
+        actix_web::rt::spawn(connection);
+        actix_web::rt::spawn(async move {
+            server.write_all(reply).await.unwrap();
+        });
+        sender
+            .send_request(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap_err()
+    }
+
+    #[actix_web::test]
+    async fn connection_closed_is_a_502() {
+        let err = AppError::from(request_against(b"").await);
+        assert_eq!(err.status_code(), StatusCode::BAD_GATEWAY);
+        assert!(err.to_string().starts_with("hyper error: "));
+    }
+
+    #[actix_web::test]
+    async fn parse_error_is_a_bad_request() {
+        let err = AppError::from(request_against(b"not http at all\r\n\r\n").await);
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,44 @@
+use serde_path_to_error::Error as PathError;
+
+use super::{AppError, FieldError};
+
+impl From<PathError<serde_json::Error>> for AppError {
+    fn from(err: PathError<serde_json::Error>) -> Self {
+        AppError::Validation(vec![FieldError {
+            field: err.path().to_string(),
+            message: err.inner().to_string(),
+        }])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Order {
+        customer: Customer,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Customer {
+        age: u8,
+    }
+
+    #[test]
+    fn bad_nested_field_is_named_by_its_path() {
+        let payload = r#"{ "customer": { "age": "forty" } }"#;
+        let deserializer = &mut serde_json::Deserializer::from_str(payload);
+        let err =
+            AppError::from(serde_path_to_error::deserialize::<_, Order>(deserializer).unwrap_err());
+
+        assert_eq!(err.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(err.field_errors()[0].field, "customer.age");
+    }
+}
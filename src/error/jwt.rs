@@ -0,0 +1,145 @@
+use jsonwebtoken::errors::{Error as JwtError, ErrorKind};
+
+use super::{AppError, HttpError};
+
+impl AppError {
+    /// For failures while issuing a token. Whatever went wrong, it was on
+    /// our side, so this is always a 500.
+    pub fn from_jwt_encode(err: JwtError) -> Self {
+        AppError::ServerError(HttpError {
+            status: 500,
+            message: format!("jwt signing error: {}", err),
+        })
+    }
+
+    /// For failures while verifying a presented token, mapped like the
+    /// `jwt-simple` and `biscuit` errors: an expired token or a bad
+    /// signature is a 401 with a code of its own, an unparseable token a
+    /// 400, any other rejection a plain 401, and our own key or algorithm
+    /// configuration failing a 500.
+    pub fn from_jwt_decode(err: JwtError) -> Self {
+        match err.kind() {
+            ErrorKind::ExpiredSignature => {
+                AppError::custom(401, "TOKEN_EXPIRED", "token has expired")
+            }
+            ErrorKind::InvalidSignature => {
+                AppError::custom(401, "SIGNATURE_INVALID", "invalid signature")
+            }
+            ErrorKind::InvalidToken
+            | ErrorKind::Base64(_)
+            | ErrorKind::Json(_)
+            | ErrorKind::Utf8(_) => AppError::BadRequest(format!("malformed token: {}", err)),
+            ErrorKind::InvalidEcdsaKey
+            | ErrorKind::InvalidRsaKey(_)
+            | ErrorKind::InvalidKeyFormat
+            | ErrorKind::InvalidAlgorithmName
+            | ErrorKind::MissingAlgorithm
+            | ErrorKind::Crypto(_) => AppError::ServerError(HttpError {
+                status: 500,
+                message: format!("jwt key error: {}", err),
+            }),
+            _ => AppError::Unauthorized,
+        }
+    }
+}
+
+/// `?` on a `jsonwebtoken` error is treated as a verification failure; use
+/// [`AppError::from_jwt_encode`] where tokens are issued.
+impl From<JwtError> for AppError {
+    fn from(err: JwtError) -> Self {
+        AppError::from_jwt_decode(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Claims {
+        sub: String,
+        exp: u64,
+    }
+
+    #[test]
+    fn signing_error_is_a_500() {
+        let claims = Claims {
+            sub: "42".into(),
+            exp: 0,
+        };
+        // an HMAC secret cannot sign an RS256 token
+        let err = jsonwebtoken::encode(
+            &Header::new(Algorithm::RS256),
+            &claims,
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap_err();
+        let err = AppError::from_jwt_encode(err);
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    fn token(exp: u64) -> String {
+        let claims = Claims {
+            sub: "42".into(),
+            exp,
+        };
+        jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap()
+    }
+
+    fn decode(token: &str) -> AppError {
+        let err = jsonwebtoken::decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(b"secret"),
+            &Validation::default(),
+        )
+        .unwrap_err();
+        AppError::from(err)
+    }
+
+    #[test]
+    fn bad_signature_is_signature_invalid() {
+        let err = jsonwebtoken::decode::<Claims>(
+            &token(u64::MAX),
+            &DecodingKey::from_secret(b"another secret"),
+            &Validation::default(),
+        )
+        .unwrap_err();
+        let err = AppError::from(err);
+        assert_eq!(err.status_code(), StatusCode::UNAUTHORIZED);
+        assert_eq!(err.code(), "SIGNATURE_INVALID");
+    }
+
+    #[test]
+    fn expired_token_is_token_expired() {
+        let err = decode(&token(1_000_000_000));
+        assert_eq!(err.status_code(), StatusCode::UNAUTHORIZED);
+        assert_eq!(err.code(), "TOKEN_EXPIRED");
+    }
+
+    #[test]
+    fn malformed_tokens_are_bad_requests() {
+        let utf8 = String::from_utf8(vec![0xff]).unwrap_err();
+        let errors = [
+            // not three segments
+            decode("not-a-token"),
+            // a header that is not base64
+            decode("!!!.e30.c2ln"),
+            // a header that is base64 but not JSON
+            decode("bm90anNvbg.e30.c2ln"),
+            AppError::from(JwtError::from(ErrorKind::Utf8(utf8))),
+        ];
+        for err in errors {
+            assert_eq!(err.status_code(), StatusCode::BAD_REQUEST, "{}", err);
+        }
+    }
+}
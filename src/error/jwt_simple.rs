@@ -0,0 +1,75 @@
+use jwt_simple::{Error as JwtSimpleError, JWTError};
+
+use super::{AppError, HttpError};
+
+impl AppError {
+    /// jwt-simple reports everything as an `anyhow::Error`, so this is an
+    /// explicit constructor rather than a blanket `From<anyhow::Error>`.
+    pub fn from_jwt_simple(err: JwtSimpleError) -> Self {
+        let Some(jwt_error) = err.downcast_ref::<JWTError>() else {
+            return AppError::Unauthorized;
+        };
+
+        match jwt_error {
+            JWTError::TokenHasExpired | JWTError::TokenIsTooOld => {
+                AppError::custom(401, "TOKEN_EXPIRED", "token has expired")
+            }
+            JWTError::InvalidSignature | JWTError::InvalidAuthenticationTag => {
+                AppError::custom(401, "SIGNATURE_INVALID", "invalid signature")
+            }
+            JWTError::NotJWT
+            | JWTError::CompactEncodingError
+            | JWTError::CWTDecodingError
+            | JWTError::HeaderTooLarge
+            | JWTError::TokenTooLong => AppError::BadRequest(format!("malformed token: {}", err)),
+            // our own keys are unusable
+            JWTError::InternalError(_)
+            | JWTError::UnsupportedRSAModulus
+            | JWTError::InvalidPublicKey
+            | JWTError::InvalidKeyPair
+            | JWTError::WeakKey => AppError::ServerError(HttpError {
+                status: 500,
+                message: format!("jwt key error: {}", err),
+            }),
+            _ => AppError::Unauthorized,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use jwt_simple::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn expired_token_is_a_401() {
+        let key = HS256Key::generate();
+        let claims = Claims::create(Duration::from_secs(60));
+        let token = key.authenticate(claims).unwrap();
+        let options = VerificationOptions {
+            artificial_time: Some(Clock::now_since_epoch() + Duration::from_days(1)),
+            ..Default::default()
+        };
+        let err = key
+            .verify_token::<NoCustomClaims>(&token, Some(options))
+            .unwrap_err();
+        let err = AppError::from_jwt_simple(err);
+        assert_eq!(err.status_code(), StatusCode::UNAUTHORIZED);
+        assert_eq!(err.code(), "TOKEN_EXPIRED");
+    }
+
+    #[test]
+    fn malformed_token_is_a_bad_request() {
+        let key = HS256Key::generate();
+        let err = key
+            .verify_token::<NoCustomClaims>("not-a-token", None)
+            .unwrap_err();
+        assert_eq!(
+            AppError::from_jwt_simple(err).status_code(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+}
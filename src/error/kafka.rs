@@ -0,0 +1,56 @@
+use rdkafka::error::KafkaError;
+use rdkafka::message::{Message, OwnedMessage};
+
+use super::AppError;
+
+/// A failed delivery report, as yielded by `FutureProducer::send`.
+impl From<(KafkaError, OwnedMessage)> for AppError {
+    fn from((err, message): (KafkaError, OwnedMessage)) -> Self {
+        AppError::custom(
+            503,
+            "KAFKA_DELIVERY_FAILED",
+            format!(
+                "delivery to topic {} partition {} failed: {}",
+                message.topic(),
+                message.partition(),
+                err
+            ),
+        )
+        .with_details(serde_json::json!({
+            "topic": message.topic(),
+            "partition": message.partition(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use rdkafka::types::RDKafkaErrorCode;
+    use rdkafka::Timestamp;
+
+    use super::*;
+
+    #[test]
+    fn failed_delivery_is_a_503_with_the_topic() {
+        let message = OwnedMessage::new(
+            Some(b"payload".to_vec()),
+            None,
+            "orders".into(),
+            Timestamp::NotAvailable,
+            3,
+            -1,
+            None,
+        );
+        let err = AppError::from((
+            KafkaError::MessageProduction(RDKafkaErrorCode::MessageTimedOut),
+            message,
+        ));
+
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        let details = err.envelope().details.unwrap();
+        assert_eq!(details["topic"], "orders");
+        assert_eq!(details["partition"], 3);
+    }
+}
@@ -0,0 +1,50 @@
+use actix_web::http::StatusCode;
+use redis::{ErrorKind, RedisError};
+
+use super::AppError;
+
+pub(super) fn status_code(err: &RedisError) -> StatusCode {
+    match err.kind() {
+        // a MOVED or ASK reaching us means the cluster client ran out of
+        // redirections mid-resharding, which settles on its own
+        ErrorKind::Moved
+        | ErrorKind::Ask
+        | ErrorKind::TryAgain
+        | ErrorKind::ClusterDown
+        | ErrorKind::MasterDown
+        | ErrorKind::BusyLoadingError
+        | ErrorKind::IoError => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl From<RedisError> for AppError {
+    fn from(err: RedisError) -> Self {
+        AppError::Redis(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::ResponseError;
+
+    use super::*;
+
+    #[test]
+    fn exhausted_redirection_is_a_retryable_503() {
+        let err = AppError::from(RedisError::from((
+            ErrorKind::Moved,
+            "An error was signalled by the server",
+            "3999 127.0.0.1:6381".to_string(),
+        )));
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn type_error_is_not_retryable() {
+        let err = AppError::from(RedisError::from((ErrorKind::TypeError, "wrong type")));
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!err.is_retryable());
+    }
+}
@@ -1,13 +1,25 @@
+use actix_web::body::BoxBody;
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use actix_web::http::Method;
 use actix_web::http::StatusCode;
-use actix_web::{error, Error, HttpResponse};
+use actix_web::{error, Error, HttpRequest, HttpResponse};
 use alcoholic_jwt::ValidationError;
-use std::env::VarError;
+use std::cell::{BorrowError, BorrowMutError};
+use std::env::{JoinPathsError, VarError};
+use std::ffi::{IntoStringError, NulError};
 use std::fmt::Formatter;
+use std::io::{Error as IoError, ErrorKind as IoErrorKind};
+use std::path::StripPrefixError;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
+use bson::oid::Error as ObjectIdError;
 use bson::ser::Error as BsonError;
-use mongodb::error::Error as MongoError;
+use mongodb::error::{Error as MongoError, ErrorKind as MongoErrorKind, GridFsErrorKind};
 
+use reqwest::header::{InvalidHeaderValue, ToStrError};
 use reqwest::Error as ReqwestError;
+use tonic::metadata::errors::{InvalidMetadataKey, InvalidMetadataValue};
 use tonic::transport::Error as GrpcConnectError;
 
 use serde::{Deserialize, Serialize};
@@ -15,6 +27,109 @@ use tonic::{Code, Status as GrpcStatusError, Status};
 
 use async_graphql::Error as GraphQLError;
 
+#[cfg(feature = "async-nats")]
+use async_nats::Error as NatsError;
+#[cfg(feature = "hyper")]
+use hyper::Error as HyperError;
+#[cfg(feature = "object_store")]
+use object_store::Error as ObjectStoreError;
+#[cfg(feature = "redis")]
+use redis::RedisError;
+#[cfg(feature = "rusqlite")]
+use rusqlite::Error as DatabaseError;
+#[cfg(feature = "rustls")]
+use rustls::Error as TlsError;
+#[cfg(feature = "sea-orm")]
+use sea_orm::DbErr;
+
+#[cfg(feature = "zip")]
+mod archive;
+#[cfg(feature = "biscuit")]
+mod biscuit;
+mod builder;
+#[cfg(any(feature = "flume", feature = "crossbeam-channel", feature = "tokio"))]
+mod channel;
+#[cfg(feature = "clap")]
+mod cli;
+#[cfg(feature = "bincode")]
+mod codec;
+mod codes;
+#[cfg(feature = "rusqlite")]
+mod database;
+mod headers;
+mod help;
+mod hooks;
+#[cfg(feature = "hyper")]
+mod http_client;
+#[cfg(feature = "serde_path_to_error")]
+mod json_path;
+#[cfg(feature = "jsonwebtoken")]
+mod jwt;
+#[cfg(feature = "jwt-simple")]
+mod jwt_simple;
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "redis")]
+mod key_value;
+#[cfg(feature = "async-nats")]
+mod nats;
+mod observability;
+#[cfg(feature = "sea-orm")]
+mod orm;
+#[cfg(feature = "password-hash")]
+mod password_hash;
+#[cfg(feature = "bb8")]
+mod pool;
+mod problem;
+#[cfg(feature = "prost-validate")]
+mod proto_validation;
+#[cfg(feature = "serde_qs")]
+mod query;
+#[cfg(feature = "governor")]
+mod rate_limit;
+mod result;
+mod retry_after;
+mod rules;
+#[cfg(feature = "config")]
+mod settings;
+#[cfg(feature = "hmac")]
+mod signature;
+#[cfg(feature = "object_store")]
+mod storage;
+#[cfg(feature = "tera")]
+mod template;
+#[cfg(any(test, feature = "test-util"))]
+mod testing;
+#[cfg(feature = "time")]
+mod time;
+#[cfg(feature = "rustls")]
+mod tls;
+#[cfg(feature = "tower")]
+mod tower;
+#[cfg(feature = "headers")]
+mod typed_headers;
+#[cfg(feature = "validator")]
+mod validation;
+#[cfg(feature = "semver")]
+mod version;
+#[cfg(feature = "xml")]
+mod xml;
+
+pub use builder::{AppErrorBuilder, ErrorKind};
+pub use headers::{StripHeaders, StripHeadersMiddleware};
+pub use hooks::{PreResponseHook, ResponseParts};
+pub use observability::{ClientDetailPolicy, LogSampler, ObservabilityConfig};
+pub use problem::ProblemDetails;
+pub use result::ResultExt;
+pub use retry_after::RetryAfterFormat;
+#[cfg(any(test, feature = "test-util"))]
+pub use testing::{representative_errors, AppResultAssertExt};
+
+#[cfg(feature = "derive")]
+pub use error_util_derive::IntoAppError;
+
+pub type AppResult<T> = Result<T, AppError>;
+
 #[derive(Debug)]
 pub enum AppError {
     Unauthorized,
@@ -30,6 +145,52 @@ pub enum AppError {
     GrpcConnectionError(GrpcConnectError),
     GrpcStatusError(GrpcStatusError),
     GraphQLError(GraphQLError),
+    BadRequest(String),
+    UnprocessableEntity(String),
+    NotFound(String),
+    Validation(Vec<FieldError>),
+    Timeout(String),
+    NotImplemented {
+        feature: Option<String>,
+    },
+    Degraded(Vec<String>),
+    ServiceUnavailable(String),
+    ResourceBusy(String),
+    NormalizedUpstream(Box<AppError>),
+    NotModified,
+    Warnings(Vec<String>),
+    OutOfRange(String),
+    RateLimited(Duration),
+    Shared(Arc<AppError>),
+    /// Any error with the extras of the `with_*` methods attached; see
+    /// [`AppError::unwrapped`].
+    Decorated(Box<Decorations>, Box<AppError>),
+    Custom {
+        status: u16,
+        code: String,
+        message: String,
+    },
+    Problem(ProblemDetails),
+    Upstream {
+        service: String,
+        code: String,
+        status: u16,
+        message: String,
+    },
+    #[cfg(feature = "rusqlite")]
+    Database(DatabaseError),
+    #[cfg(feature = "object_store")]
+    ObjectStore(ObjectStoreError),
+    #[cfg(feature = "sea-orm")]
+    SeaOrm(DbErr),
+    #[cfg(feature = "hyper")]
+    Hyper(HyperError),
+    #[cfg(feature = "rustls")]
+    Tls(TlsError),
+    #[cfg(feature = "redis")]
+    Redis(RedisError),
+    #[cfg(feature = "async-nats")]
+    Nats(NatsError),
 }
 
 impl AppError {
@@ -55,11 +216,72 @@ impl AppError {
         }
     }
 
+    /// A bad URI or user agent, or TLS material tonic could not load, is our
+    /// own misconfiguration rather than a dependency being down, so it must
+    /// not read as a transient 503. tonic keeps the error kind private, so
+    /// these are told apart by tonic's own messages for them; a failed
+    /// handshake with the peer is still a 503.
+    fn is_grpc_misconfiguration(err: &GrpcConnectError) -> bool {
+        const INVALID_ENDPOINT: [&str; 2] =
+            ["invalid URI", "user agent is not a valid header value"];
+        const INVALID_TLS_CONFIG: [&str; 2] = [
+            "Error parsing TLS certificate",
+            "Error parsing TLS private key",
+        ];
+
+        let message = err.to_string();
+        if INVALID_ENDPOINT.contains(&message.as_str()) {
+            return true;
+        }
+        std::error::Error::source(err).is_some_and(|source| {
+            let message = source.to_string();
+            INVALID_TLS_CONFIG
+                .iter()
+                .any(|marker| message.starts_with(marker))
+        })
+    }
+
+    fn grpc_code_for_status(status: StatusCode) -> Code {
+        match status {
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => Code::InvalidArgument,
+            StatusCode::UNAUTHORIZED => Code::Unauthenticated,
+            StatusCode::FORBIDDEN => Code::PermissionDenied,
+            StatusCode::NOT_FOUND => Code::NotFound,
+            StatusCode::CONFLICT => Code::AlreadyExists,
+            StatusCode::PRECONDITION_FAILED => Code::FailedPrecondition,
+            StatusCode::RANGE_NOT_SATISFIABLE => Code::OutOfRange,
+            StatusCode::TOO_MANY_REQUESTS => Code::ResourceExhausted,
+            StatusCode::NOT_IMPLEMENTED => Code::Unimplemented,
+            StatusCode::SERVICE_UNAVAILABLE => Code::Unavailable,
+            StatusCode::GATEWAY_TIMEOUT => Code::DeadlineExceeded,
+            status if status.is_client_error() => Code::InvalidArgument,
+            _ => Code::Internal,
+        }
+    }
+
+    /// Transaction failures the driver labels as safe to retry as a whole,
+    /// and change-stream failures the server says can be resumed from the
+    /// last token. An invalidated stream carries no such label and stays a
+    /// 500: resuming it would fail again.
+    fn is_transient_mongo_error(err: &MongoError) -> bool {
+        err.contains_label("TransientTransactionError")
+            || err.contains_label("UnknownTransactionCommitResult")
+            || err.contains_label("ResumableChangeStreamError")
+    }
+
     pub fn convert_app_error_to_graphql_error(&self) -> GraphQLError {
-        GraphQLError {
-            message: self.to_string(),
-            source: None,
-            extensions: None,
+        match self.unwrapped() {
+            // keep whatever the wrapped error carried for the gateway
+            AppError::GraphQLError(error) => GraphQLError {
+                message: self.to_string(),
+                source: error.source.clone(),
+                extensions: error.extensions.clone(),
+            },
+            _ => GraphQLError {
+                message: self.to_string(),
+                source: None,
+                extensions: None,
+            },
         }
     }
 
@@ -71,8 +293,10 @@ impl AppError {
         }
     }
 
+    /// The gRPC status for this error. The intended HTTP status travels along
+    /// as `x-http-status` metadata, since the gRPC code alone is lossy.
     pub fn convert_app_error_to_grpc_status(&self) -> Status {
-        match self {
+        let mut status = match self {
             AppError::Unauthorized => Status::unauthenticated("not authorized".to_string()),
             AppError::ReqwestAPIError(error) => Status::internal(error.to_string()),
             AppError::JwksError(error) => Status::permission_denied(error.to_string()),
@@ -82,10 +306,558 @@ impl AppError {
             AppError::BsonError(error) => Status::failed_precondition(error.to_string()),
             AppError::AppError(error) => Status::failed_precondition(error.to_string()),
             AppError::StandardError(error) => Status::invalid_argument(error.to_string()),
+            AppError::GrpcConnectionError(error) if Self::is_grpc_misconfiguration(error) => {
+                Status::internal(error.to_string())
+            }
             AppError::GrpcConnectionError(error) => Status::unavailable(error.to_string()),
             AppError::GrpcStatusError(error) => Status::new(error.code(), error.message()),
             AppError::GraphQLError(error) => Status::internal(error.clone().message),
+            AppError::Mongo(error) if Self::is_transient_mongo_error(error) => {
+                Status::unavailable(error.to_string())
+            }
             AppError::Mongo(error) => Status::failed_precondition(error.to_string()),
+            AppError::BadRequest(msg) => Status::invalid_argument(msg.to_string()),
+            AppError::UnprocessableEntity(msg) => Status::invalid_argument(msg.to_string()),
+            AppError::NotFound(msg) => Status::not_found(msg.to_string()),
+            #[cfg(feature = "grpc-details")]
+            AppError::Validation(errors) => {
+                use tonic_types::{ErrorDetails, StatusExt};
+
+                // the same violations as the envelope's `errors`, for clients
+                // that read `google.rpc.BadRequest`
+                let mut details = ErrorDetails::new();
+                for error in errors {
+                    details.add_bad_request_violation(&error.field, &error.message);
+                }
+                Status::with_error_details(Code::InvalidArgument, self.to_string(), details)
+            }
+            #[cfg(not(feature = "grpc-details"))]
+            AppError::Validation(_) => Status::invalid_argument(self.to_string()),
+            AppError::Timeout(msg) => Status::deadline_exceeded(msg.to_string()),
+            AppError::NotImplemented { .. } => Status::unimplemented(self.error_response()),
+            AppError::Degraded(components) => {
+                Status::unavailable(format!("degraded: {}", components.join(", ")))
+            }
+            AppError::ServiceUnavailable(msg) => Status::unavailable(msg.to_string()),
+            AppError::ResourceBusy(_) => Status::unavailable(self.error_response()),
+            AppError::NormalizedUpstream(_) => Status::unavailable("bad gateway".to_string()),
+            AppError::NotModified => Status::ok("not modified".to_string()),
+            AppError::Warnings(warnings) => Status::ok(warnings.join(", ")),
+            AppError::OutOfRange(msg) => Status::out_of_range(msg.to_string()),
+            AppError::RateLimited(_) => Status::resource_exhausted("rate limited".to_string()),
+            AppError::Decorated(_, error) => error.convert_app_error_to_grpc_status(),
+            AppError::Shared(error) => error.convert_app_error_to_grpc_status(),
+            AppError::Custom { message, .. } => Status::new(
+                Self::grpc_code_for_status(error::ResponseError::status_code(self)),
+                message.to_string(),
+            ),
+            AppError::Problem(problem) => Status::new(
+                Self::grpc_code_for_status(error::ResponseError::status_code(self)),
+                problem.detail.to_string(),
+            ),
+            AppError::Upstream { .. } => Status::new(
+                Self::grpc_code_for_status(error::ResponseError::status_code(self)),
+                self.to_string(),
+            ),
+            #[cfg(feature = "rusqlite")]
+            AppError::Database(error) => match database::status_code(error) {
+                StatusCode::NOT_FOUND => Status::not_found(error.to_string()),
+                StatusCode::CONFLICT => Status::already_exists(error.to_string()),
+                _ => Status::internal(error.to_string()),
+            },
+            #[cfg(feature = "object_store")]
+            AppError::ObjectStore(error) => match storage::status_code(error) {
+                StatusCode::NOT_FOUND => Status::not_found(error.to_string()),
+                StatusCode::FORBIDDEN => Status::permission_denied(error.to_string()),
+                StatusCode::SERVICE_UNAVAILABLE => Status::unavailable(error.to_string()),
+                _ => Status::internal(error.to_string()),
+            },
+            #[cfg(feature = "sea-orm")]
+            AppError::SeaOrm(error) => match orm::status_code(error) {
+                StatusCode::NOT_FOUND => Status::not_found(error.to_string()),
+                StatusCode::CONFLICT => Status::already_exists(error.to_string()),
+                StatusCode::SERVICE_UNAVAILABLE => Status::unavailable(error.to_string()),
+                _ => Status::internal(error.to_string()),
+            },
+            #[cfg(feature = "hyper")]
+            AppError::Hyper(error) => match http_client::status_code(error) {
+                StatusCode::BAD_REQUEST => Status::invalid_argument(error.to_string()),
+                StatusCode::GATEWAY_TIMEOUT => Status::deadline_exceeded(error.to_string()),
+                _ => Status::unavailable(error.to_string()),
+            },
+            #[cfg(feature = "rustls")]
+            AppError::Tls(error) => match tls::status_code(error) {
+                StatusCode::BAD_GATEWAY => Status::unavailable(error.to_string()),
+                _ => Status::internal(error.to_string()),
+            },
+            #[cfg(feature = "redis")]
+            AppError::Redis(error) => match key_value::status_code(error) {
+                StatusCode::SERVICE_UNAVAILABLE => Status::unavailable(error.to_string()),
+                _ => Status::internal(error.to_string()),
+            },
+            #[cfg(feature = "async-nats")]
+            AppError::Nats(error) => match nats::status_code(error) {
+                StatusCode::SERVICE_UNAVAILABLE => Status::unavailable(error.to_string()),
+                StatusCode::GATEWAY_TIMEOUT => Status::deadline_exceeded(error.to_string()),
+                _ => Status::internal(error.to_string()),
+            },
+        };
+
+        let http_status = error::ResponseError::status_code(self).as_u16();
+        status
+            .metadata_mut()
+            .insert("x-http-status", http_status.into());
+        if let Some(value) = self
+            .correlation_id()
+            .and_then(|correlation_id| correlation_id.parse().ok())
+        {
+            status.metadata_mut().insert("x-correlation-id", value);
+        }
+        status
+    }
+
+    /// Terminates a server-streaming response that failed mid-stream: send
+    /// the result as the stream's last item, e.g.
+    /// `yield Err(err.into_stream_status())`. Maps exactly like the unary
+    /// conversion.
+    pub fn into_stream_status(self) -> Status {
+        self.convert_app_error_to_grpc_status()
+    }
+
+    /// Like [`AppError::convert_app_error_to_grpc_status`], with
+    /// `correlation_id` sent as `x-correlation-id` metadata in place of the
+    /// one in the error's context. An id that is not a valid metadata value
+    /// is dropped.
+    pub fn convert_app_error_to_grpc_status_with_correlation_id(
+        &self,
+        correlation_id: &str,
+    ) -> Status {
+        let mut status = self.convert_app_error_to_grpc_status();
+        if let Ok(value) = correlation_id.parse() {
+            status.metadata_mut().insert("x-correlation-id", value);
+        }
+        status
+    }
+
+    /// A lock or similar resource could not be acquired in time, e.g. a
+    /// `tokio::sync::Mutex::lock` wrapped in a timeout, or a failed
+    /// `try_lock` under contention. Rendered as a 503 with `Retry-After: 1`.
+    pub fn resource_busy(resource: impl Into<String>) -> Self {
+        AppError::ResourceBusy(resource.into())
+    }
+
+    /// For stubbed endpoints; rendered as a 501.
+    pub fn not_implemented(feature: Option<String>) -> Self {
+        AppError::NotImplemented { feature }
+    }
+
+    /// Partial outage: the listed dependencies are unhealthy but the service
+    /// itself is up. Rendered as a 503 with a `components` array.
+    pub fn degraded(components: Vec<String>) -> Self {
+        AppError::Degraded(components)
+    }
+
+    /// A conditional request (`If-None-Match` / `If-Modified-Since`) matched;
+    /// rendered as a bodyless 304.
+    pub fn not_modified() -> Self {
+        AppError::NotModified
+    }
+
+    /// Not an error: the request succeeded, but with non-fatal warnings.
+    /// Rendered as a 200 whose envelope carries a `warnings` array.
+    pub fn warnings(warnings: Vec<String>) -> Self {
+        AppError::Warnings(warnings)
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::Unauthorized => "Unauthorized",
+            AppError::ReqwestAPIError(_) => "ReqwestAPIError",
+            AppError::JwksError(_) => "JwksError",
+            AppError::Mongo(_) => "Mongo",
+            AppError::ConfigError(_) => "ConfigError",
+            AppError::ClientError(_) => "ClientError",
+            AppError::ServerError(_) => "ServerError",
+            AppError::BsonError(_) => "BsonError",
+            AppError::AppError(_) => "AppError",
+            AppError::StandardError(_) => "StandardError",
+            AppError::GrpcConnectionError(_) => "GrpcConnectionError",
+            AppError::GrpcStatusError(_) => "GrpcStatusError",
+            AppError::GraphQLError(_) => "GraphQLError",
+            AppError::BadRequest(_) => "BadRequest",
+            AppError::UnprocessableEntity(_) => "UnprocessableEntity",
+            AppError::NotFound(_) => "NotFound",
+            AppError::Validation(_) => "Validation",
+            AppError::Timeout(_) => "Timeout",
+            AppError::NotImplemented { .. } => "NotImplemented",
+            AppError::Degraded(_) => "Degraded",
+            AppError::ServiceUnavailable(_) => "ServiceUnavailable",
+            AppError::ResourceBusy(_) => "ResourceBusy",
+            AppError::NormalizedUpstream(_) => "NormalizedUpstream",
+            AppError::NotModified => "NotModified",
+            AppError::Warnings(_) => "Warnings",
+            AppError::OutOfRange(_) => "OutOfRange",
+            AppError::RateLimited(_) => "RateLimited",
+            AppError::Decorated(_, error) => error.kind(),
+            AppError::Shared(error) => error.kind(),
+            AppError::Custom { .. } => "Custom",
+            AppError::Problem(_) => "Problem",
+            AppError::Upstream { .. } => "Upstream",
+            #[cfg(feature = "rusqlite")]
+            AppError::Database(_) => "Database",
+            #[cfg(feature = "object_store")]
+            AppError::ObjectStore(_) => "ObjectStore",
+            #[cfg(feature = "sea-orm")]
+            AppError::SeaOrm(_) => "SeaOrm",
+            #[cfg(feature = "hyper")]
+            AppError::Hyper(_) => "Hyper",
+            #[cfg(feature = "rustls")]
+            AppError::Tls(_) => "Tls",
+            #[cfg(feature = "redis")]
+            AppError::Redis(_) => "Redis",
+            #[cfg(feature = "async-nats")]
+            AppError::Nats(_) => "Nats",
+        }
+    }
+
+    /// Rebuilds the error a dependency returned, keeping its code and status
+    /// verbatim so clients behind a gateway still see the root cause.
+    pub fn from_upstream_response(
+        service: impl Into<String>,
+        status: u16,
+        response: AppErrorResponse,
+    ) -> Self {
+        AppError::Upstream {
+            service: service.into(),
+            code: response.code,
+            status,
+            message: response.message,
+        }
+    }
+
+    /// An error with a caller-chosen status and code, e.g. for a domain
+    /// error enum mapped through `#[derive(IntoAppError)]`.
+    pub fn custom(status: u16, code: impl Into<String>, message: impl Into<String>) -> Self {
+        AppError::Custom {
+            status,
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+
+    /// An explicit cache miss, for callers that want it to read as a 404.
+    pub fn cache_miss(key: impl std::fmt::Display) -> Self {
+        AppError::NotFound(format!("no cached entry for {}", key))
+    }
+
+    /// For headers read from the client's request, where a bad value is the
+    /// caller's fault rather than ours.
+    pub fn invalid_client_header(name: &str, err: impl std::fmt::Display) -> Self {
+        AppError::BadRequest(format!("invalid {} header: {}", name, err))
+    }
+
+    /// Pins the id shown to the client in the body and the `X-Error-Id`
+    /// header and recorded on the log event. Every response gets an id
+    /// anyway; pinning it makes it known before the first response is built.
+    /// Calling it again keeps the first id.
+    pub fn with_error_id(self) -> Self {
+        if self.error_id().is_some() {
+            self
+        } else {
+            self.decorate(|decorations| {
+                decorations.id.get_or_init(Self::new_error_id);
+            })
+        }
+    }
+
+    fn new_error_id() -> String {
+        uuid::Uuid::new_v4().to_string()
+    }
+
+    /// The id a response is built with: the pinned one, else one generated
+    /// on first use and kept in the error's decorations, so that rendering
+    /// the same error again repeats it. A bare variant has nowhere to keep
+    /// it and gets a fresh id per response; pin it, or attach anything
+    /// through a `with_*` method, to keep one across responses.
+    fn response_id(&self) -> String {
+        match self {
+            AppError::Decorated(decorations, error) => match error.error_id() {
+                Some(id) => id.to_string(),
+                None => decorations.id.get_or_init(Self::new_error_id).clone(),
+            },
+            AppError::Shared(error) => error.response_id(),
+            _ => Self::new_error_id(),
+        }
+    }
+
+    /// Applies `f` to the error's decorations, adding the wrapper if there is
+    /// none yet, so that decorations never nest.
+    fn decorate(self, f: impl FnOnce(&mut Decorations)) -> Self {
+        match self {
+            AppError::Decorated(mut decorations, error) => {
+                f(&mut decorations);
+                AppError::Decorated(decorations, error)
+            }
+            _ => {
+                let mut decorations = Box::<Decorations>::default();
+                f(&mut decorations);
+                AppError::Decorated(decorations, Box::new(self))
+            }
+        }
+    }
+
+    /// Records how long the failed operation took, sent to the client as
+    /// `Server-Timing: app;dur=<ms>`. Calling it again replaces the duration.
+    pub fn with_duration(self, duration: Duration) -> Self {
+        self.decorate(|decorations| decorations.duration = Some(duration))
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        match self {
+            AppError::Decorated(decorations, error) => {
+                decorations.duration.or_else(|| error.duration())
+            }
+            AppError::Shared(error) => error.duration(),
+            _ => None,
+        }
+    }
+
+    pub fn error_id(&self) -> Option<&str> {
+        match self {
+            AppError::Decorated(decorations, error) => decorations
+                .id
+                .get()
+                .map(String::as_str)
+                .or_else(|| error.error_id()),
+            AppError::Shared(error) => error.error_id(),
+            _ => None,
+        }
+    }
+
+    /// Points the error at an input field, e.g. a 409 for "email already
+    /// taken". Listed under `errors` in the envelope whatever the variant.
+    pub fn with_field(self, field: impl Into<String>, message: impl Into<String>) -> Self {
+        let field = FieldError {
+            field: field.into(),
+            message: message.into(),
+        };
+        match self {
+            AppError::Validation(mut errors) => {
+                errors.push(field);
+                AppError::Validation(errors)
+            }
+            _ => self.decorate(|decorations| decorations.fields.push(field)),
+        }
+    }
+
+    /// Arbitrary structured data for the client, sent as the envelope's
+    /// `details`. Calling it again replaces the details.
+    pub fn with_details(self, details: serde_json::Value) -> Self {
+        self.decorate(|decorations| decorations.details = Some(details))
+    }
+
+    fn details(&self) -> Option<&serde_json::Value> {
+        match self {
+            AppError::Decorated(decorations, error) => {
+                decorations.details.as_ref().or_else(|| error.details())
+            }
+            AppError::Shared(error) => error.details(),
+            _ => None,
+        }
+    }
+
+    /// A key/value pair recorded on the log event as `error.context` and in
+    /// [`AppError::to_log_json`], e.g. the id of the order being processed.
+    /// Never sent to the client.
+    pub fn with_context(self, key: impl Into<String>, value: impl ToString) -> Self {
+        let pair = (key.into(), value.to_string());
+        self.decorate(|decorations| decorations.context.push(pair))
+    }
+
+    /// Every context pair attached to this error or the one it wraps.
+    fn context(&self) -> Vec<(String, String)> {
+        match self {
+            AppError::Decorated(decorations, error) => {
+                let mut context = error.context();
+                context.extend(decorations.context.iter().cloned());
+                context
+            }
+            AppError::Shared(error) => error.context(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The `correlation_id` context entry, echoed to gRPC clients as
+    /// `x-correlation-id` metadata; the last one attached wins.
+    fn correlation_id(&self) -> Option<String> {
+        self.context()
+            .into_iter()
+            .rev()
+            .find_map(|(key, value)| (key == "correlation_id").then_some(value))
+    }
+
+    /// Tells the client when to try again with a `Retry-After` header,
+    /// keeping the error's own status and code. Calling it again replaces
+    /// the delay.
+    pub fn with_retry_after(self, delay: Duration) -> Self {
+        self.decorate(|decorations| decorations.retry_after = Some(delay))
+    }
+
+    /// The delay sent as `Retry-After`, if any.
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            AppError::Decorated(decorations, error) => {
+                decorations.retry_after.or_else(|| error.retry_after())
+            }
+            AppError::Shared(error) => error.retry_after(),
+            AppError::RateLimited(delay) => Some(*delay),
+            // both clear up on their own, so tell clients when to come back
+            AppError::ResourceBusy(_) | AppError::ServiceUnavailable(_) => {
+                Some(Duration::from_secs(1))
+            }
+            _ => None,
+        }
+    }
+
+    /// Every field error attached to this error or the one it wraps.
+    fn field_errors(&self) -> Vec<FieldError> {
+        match self {
+            AppError::Validation(errors) => errors.clone(),
+            AppError::Decorated(decorations, error) => {
+                let mut errors = error.field_errors();
+                errors.extend(decorations.fields.iter().cloned());
+                errors
+            }
+            AppError::Shared(error) => error.field_errors(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The error itself, without its decorations or the [`AppError::Shared`]
+    /// wrapper. Match on this rather than on the error directly, since any
+    /// `with_*` call wraps the variant, e.g.
+    /// `matches!(err.unwrapped(), AppError::NotFound(_))`.
+    pub fn unwrapped(&self) -> &AppError {
+        match self {
+            AppError::Decorated(_, error) => error.unwrapped(),
+            AppError::Shared(error) => error.unwrapped(),
+            _ => self,
+        }
+    }
+
+    /// Turns an upstream 5xx into a 502 for our own clients. The original
+    /// error is kept as the source, so it still shows up in the logs.
+    pub fn normalize_upstream(self) -> AppError {
+        let upstream_failed = match self.unwrapped() {
+            AppError::ServerError(_) => true,
+            AppError::ReqwestAPIError(err) => err.status().is_some_and(|s| s.is_server_error()),
+            AppError::GrpcStatusError(status) => {
+                self.convert_grpc_error_to_status(status).is_server_error()
+            }
+            _ => false,
+        };
+
+        match self {
+            // keep the decorations outermost, where the id and fields are read
+            AppError::Decorated(decorations, error) if upstream_failed => {
+                AppError::Decorated(decorations, Box::new(error.normalize_upstream()))
+            }
+            _ if upstream_failed => AppError::NormalizedUpstream(Box::new(self)),
+            _ => self,
+        }
+    }
+
+    /// Machine readable code, e.g. `NOT_FOUND`. Derived from the HTTP status
+    /// unless the error carries a code of its own.
+    pub fn code(&self) -> String {
+        codes::apply_policy(self.raw_code())
+    }
+
+    fn raw_code(&self) -> String {
+        let explicit = match self.unwrapped() {
+            AppError::Upstream { code, .. } | AppError::Custom { code, .. } => Some(code.clone()),
+            AppError::Problem(problem) => problem.code().map(Into::into),
+            AppError::StandardError(msg) => rules::standard_error_rule(msg).map(|(_, code)| code),
+            _ => None,
+        };
+        if let Some(code) = explicit {
+            return code;
+        }
+
+        Self::code_for_status(error::ResponseError::status_code(self))
+    }
+
+    /// `NOT_FOUND` for a 404, and so on.
+    fn code_for_status(status: StatusCode) -> String {
+        status
+            .canonical_reason()
+            .unwrap_or("UNKNOWN")
+            .to_uppercase()
+            .replace([' ', '-'], "_")
+    }
+
+    /// Whether the same request may succeed if the client tries again later.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            error::ResponseError::status_code(self),
+            StatusCode::TOO_MANY_REQUESTS
+                | StatusCode::SERVICE_UNAVAILABLE
+                | StatusCode::GATEWAY_TIMEOUT
+        )
+    }
+
+    /// `client` for 4xx errors, `server` for everything else.
+    pub fn category(&self) -> &'static str {
+        if error::ResponseError::status_code(self).is_client_error() {
+            "client"
+        } else {
+            "server"
+        }
+    }
+
+    /// Combines the outcomes of two independent checks into the more severe
+    /// one: a 5xx beats a 4xx, which beats anything else. On a tie `self`
+    /// wins. The messages of both are kept, winner first, as the envelope's
+    /// `details.messages`, next to any details the winner already had (a
+    /// non-object value moves to `details.value`); merging a merged error
+    /// flattens the list.
+    pub fn merge(self, other: AppError) -> AppError {
+        fn severity(error: &AppError) -> u8 {
+            let status = error::ResponseError::status_code(error);
+            if status.is_server_error() {
+                2
+            } else if status.is_client_error() {
+                1
+            } else {
+                0
+            }
+        }
+
+        let (winner, loser) = if severity(&other) > severity(&self) {
+            (other, self)
+        } else {
+            (self, other)
+        };
+        let mut messages = winner.merged_messages();
+        messages.extend(loser.merged_messages());
+
+        let mut details = match winner.details() {
+            Some(serde_json::Value::Object(details)) => details.clone(),
+            Some(value) => serde_json::Map::from_iter([("value".to_string(), value.clone())]),
+            None => serde_json::Map::new(),
+        };
+        details.insert("messages".into(), messages.into());
+        winner.with_details(details.into())
+    }
+
+    fn merged_messages(&self) -> Vec<serde_json::Value> {
+        match self
+            .details()
+            .and_then(|details| details.get("messages"))
+            .and_then(serde_json::Value::as_array)
+        {
+            Some(messages) => messages.clone(),
+            None => vec![self.public_message().into()],
         }
     }
 }
@@ -113,8 +885,87 @@ impl std::fmt::Display for AppError {
             }
 
             AppError::Mongo(error) => {
-                write!(f, "mongo error: {}", error.clone().to_string())
+                write!(f, "mongo error: {}", error)
             }
+            AppError::BadRequest(msg) => write!(f, "bad request: {}", msg),
+            AppError::UnprocessableEntity(msg) => write!(f, "unprocessable entity: {}", msg),
+            AppError::NotFound(msg) => write!(f, "not found: {}", msg),
+            AppError::Validation(errors) => {
+                let fields: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                write!(f, "validation error: {}", fields.join(", "))
+            }
+            AppError::Timeout(msg) => write!(f, "timeout: {}", msg),
+            AppError::NotImplemented { .. } => write!(f, "{}", self.error_response()),
+            AppError::Degraded(components) => write!(f, "degraded: {}", components.join(", ")),
+            AppError::ServiceUnavailable(msg) => write!(f, "service unavailable: {}", msg),
+            AppError::ResourceBusy(resource) => write!(f, "resource busy: {}", resource),
+            AppError::NormalizedUpstream(err) => write!(f, "bad gateway: {}", err),
+            AppError::NotModified => write!(f, "not modified"),
+            AppError::Warnings(warnings) => write!(f, "warnings: {}", warnings.join(", ")),
+            AppError::OutOfRange(msg) => write!(f, "out of range: {}", msg),
+            AppError::RateLimited(retry_after) => {
+                write!(f, "rate limited: retry after {:?}", retry_after)
+            }
+            AppError::Decorated(_, error) => write!(f, "{}", error),
+            AppError::Shared(error) => write!(f, "{}", error),
+            AppError::Custom { code, message, .. } => write!(f, "{}: {}", code, message),
+            AppError::Problem(problem) => write!(f, "{}: {}", problem.title, problem.detail),
+            AppError::Upstream {
+                service,
+                code,
+                status,
+                message,
+            } => write!(
+                f,
+                "upstream {} error: {} {} {}",
+                service, status, code, message
+            ),
+            #[cfg(feature = "rusqlite")]
+            AppError::Database(err) => write!(f, "database error: {}", err),
+            #[cfg(feature = "object_store")]
+            AppError::ObjectStore(err) => write!(f, "object store error: {}", err),
+            #[cfg(feature = "sea-orm")]
+            AppError::SeaOrm(err) => write!(f, "database error: {}", err),
+            #[cfg(feature = "hyper")]
+            AppError::Hyper(err) => write!(f, "hyper error: {}", err),
+            #[cfg(feature = "rustls")]
+            AppError::Tls(err) => write!(f, "tls error: {}", err),
+            #[cfg(feature = "redis")]
+            AppError::Redis(err) => write!(f, "redis error: {}", err),
+            #[cfg(feature = "async-nats")]
+            AppError::Nats(err) => write!(f, "nats error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::ReqwestAPIError(err) => Some(err),
+            AppError::JwksError(err) => Some(err),
+            AppError::Mongo(err) => Some(err),
+            AppError::ConfigError(err) => Some(err),
+            AppError::BsonError(err) => Some(err),
+            AppError::GrpcConnectionError(err) => Some(err),
+            AppError::GrpcStatusError(err) => Some(err),
+            AppError::NormalizedUpstream(err) => Some(err.as_ref()),
+            AppError::Decorated(_, err) => err.source(),
+            AppError::Shared(err) => err.source(),
+            #[cfg(feature = "rusqlite")]
+            AppError::Database(err) => Some(err),
+            #[cfg(feature = "object_store")]
+            AppError::ObjectStore(err) => Some(err),
+            #[cfg(feature = "sea-orm")]
+            AppError::SeaOrm(err) => Some(err),
+            #[cfg(feature = "hyper")]
+            AppError::Hyper(err) => Some(err),
+            #[cfg(feature = "rustls")]
+            AppError::Tls(err) => Some(err),
+            #[cfg(feature = "redis")]
+            AppError::Redis(err) => Some(err),
+            #[cfg(feature = "async-nats")]
+            AppError::Nats(err) => Some(err.as_ref()),
+            _ => None,
         }
     }
 }
@@ -123,6 +974,10 @@ impl error::ResponseError for AppError {
     fn status_code(&self) -> StatusCode {
         match self {
             AppError::Unauthorized => StatusCode::UNAUTHORIZED,
+            // the upstream answered, but its body was truncated or corrupt
+            AppError::ReqwestAPIError(err) if err.is_body() || err.is_decode() => {
+                StatusCode::BAD_GATEWAY
+            }
             AppError::ReqwestAPIError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::JwksError(_) => StatusCode::UNAUTHORIZED,
             AppError::ConfigError(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -130,22 +985,220 @@ impl error::ResponseError for AppError {
             AppError::ServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::BsonError(_) => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::AppError(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            AppError::StandardError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::StandardError(msg) => rules::standard_error_rule(msg)
+                .map_or(StatusCode::INTERNAL_SERVER_ERROR, |(status, _)| status),
+            AppError::GrpcConnectionError(error) if Self::is_grpc_misconfiguration(error) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
             AppError::GrpcConnectionError(_) => StatusCode::SERVICE_UNAVAILABLE,
             AppError::GrpcStatusError(status) => self.convert_grpc_error_to_status(status),
             AppError::GraphQLError(_error) => StatusCode::INTERNAL_SERVER_ERROR,
-            AppError::Mongo(_error) => StatusCode::INTERNAL_SERVER_ERROR
+            AppError::Mongo(error) if Self::is_transient_mongo_error(error) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            AppError::Mongo(_error) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            AppError::NotImplemented { .. } => StatusCode::NOT_IMPLEMENTED,
+            AppError::Degraded(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::ResourceBusy(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::NormalizedUpstream(_) => StatusCode::BAD_GATEWAY,
+            AppError::NotModified => StatusCode::NOT_MODIFIED,
+            AppError::Warnings(_) => StatusCode::OK,
+            AppError::OutOfRange(_) => StatusCode::RANGE_NOT_SATISFIABLE,
+            AppError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Decorated(_, error) => error.status_code(),
+            AppError::Shared(error) => error.status_code(),
+            AppError::Custom { status, .. } => {
+                StatusCode::from_u16(*status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+            AppError::Problem(problem) => {
+                StatusCode::from_u16(problem.status).unwrap_or(StatusCode::BAD_GATEWAY)
+            }
+            AppError::Upstream { status, .. } => {
+                StatusCode::from_u16(*status).unwrap_or(StatusCode::BAD_GATEWAY)
+            }
+            #[cfg(feature = "rusqlite")]
+            AppError::Database(err) => database::status_code(err),
+            #[cfg(feature = "object_store")]
+            AppError::ObjectStore(err) => storage::status_code(err),
+            #[cfg(feature = "sea-orm")]
+            AppError::SeaOrm(err) => orm::status_code(err),
+            #[cfg(feature = "hyper")]
+            AppError::Hyper(err) => http_client::status_code(err),
+            #[cfg(feature = "rustls")]
+            AppError::Tls(err) => tls::status_code(err),
+            #[cfg(feature = "redis")]
+            AppError::Redis(err) => key_value::status_code(err),
+            #[cfg(feature = "async-nats")]
+            AppError::Nats(err) => nats::status_code(err),
         }
     }
 
     fn error_response(&self) -> HttpResponse {
-        HttpResponse::build(self.status_code()).json(AppErrorResponse {
-            message: self.error_response(),
-        })
+        let id = self.response_id();
+        // neither is a failure worth an event
+        if !matches!(
+            self.unwrapped(),
+            AppError::Warnings(_) | AppError::NotModified
+        ) {
+            self.report(&id);
+        }
+
+        let mut response = HttpResponse::build(self.status_code());
+        #[cfg(feature = "otel")]
+        if let Some(traceparent) = observability::traceparent() {
+            response.insert_header(("traceparent", traceparent));
+        }
+        response.insert_header(("X-Error-Id", id.as_str()));
+        if let Some(duration) = self.duration() {
+            response.insert_header(("Server-Timing", format!("app;dur={}", duration.as_millis())));
+        }
+        if let Some(delay) = self.retry_after() {
+            response.insert_header(("Retry-After", retry_after::header_value(delay)));
+        }
+
+        let response = match self.unwrapped() {
+            AppError::NotModified => response.finish(),
+            _ => response.json(self.envelope_with_id(Some(id))),
+        };
+        let mut response = hooks::apply(self, response);
+        headers::strip(response.headers_mut());
+        response
     }
 }
 
 impl AppError {
+    /// Like `error_response`, but aware of the request it answers: a `HEAD`
+    /// request gets the same status and headers with an empty body, and a
+    /// `Prefer: return=minimal` one gets the status alone.
+    pub fn error_response_with_request(&self, req: &HttpRequest) -> HttpResponse {
+        let mut response = error::ResponseError::error_response(self);
+        if Self::prefers_minimal(req) {
+            let headers = response.headers_mut();
+            headers.remove(header::CONTENT_TYPE);
+            headers.insert(
+                HeaderName::from_static("preference-applied"),
+                HeaderValue::from_static("return=minimal"),
+            );
+            response.set_body(BoxBody::new(()))
+        } else if req.method() == Method::HEAD {
+            response.set_body(BoxBody::new(()))
+        } else {
+            response
+        }
+    }
+
+    fn prefers_minimal(req: &HttpRequest) -> bool {
+        req.headers()
+            .get_all("prefer")
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|preference| preference.trim().eq_ignore_ascii_case("return=minimal"))
+    }
+
+    /// Both transports' representations at once, for logic served over HTTP
+    /// and gRPC alike. The gRPC status carries the envelope's message and its
+    /// `code` as `x-error-code` metadata, so the two never disagree.
+    pub fn dual_response(&self) -> (HttpResponse, Status) {
+        let grpc = self.convert_app_error_to_grpc_status();
+        let mut metadata = grpc.metadata().clone();
+        if let Ok(code) = self.code().parse() {
+            metadata.insert("x-error-code", code);
+        }
+
+        (
+            error::ResponseError::error_response(self),
+            Status::with_metadata(grpc.code(), self.public_message(), metadata),
+        )
+    }
+
+    /// The JSON body sent to clients. Its `id` is only set once pinned with
+    /// [`AppError::with_error_id`], or once a response has been built for a
+    /// decorated error.
+    pub fn envelope(&self) -> AppErrorResponse {
+        self.envelope_with_id(self.error_id().map(Into::into))
+    }
+
+    fn envelope_with_id(&self, id: Option<String>) -> AppErrorResponse {
+        let components = match self.unwrapped() {
+            AppError::Degraded(components) => Some(components.clone()),
+            _ => None,
+        };
+
+        let warnings = match self.unwrapped() {
+            AppError::Warnings(warnings) => Some(warnings.clone()),
+            _ => None,
+        };
+
+        let errors = Some(self.field_errors()).filter(|errors| !errors.is_empty());
+
+        let causes = if observability::production_mode() {
+            None
+        } else {
+            self.causes()
+        };
+
+        AppErrorResponse {
+            message: self.public_message(),
+            code: self.code(),
+            id,
+            components,
+            errors,
+            warnings,
+            details: self.details().cloned(),
+            help_url: self.help_url(),
+            causes,
+        }
+    }
+
+    /// The envelope as a Server-Sent Events `error` frame.
+    pub fn to_sse_event(&self) -> String {
+        let data = serde_json::to_string(&self.envelope())
+            .unwrap_or_else(|_| r#"{"message":"internal server error"}"#.into());
+        format!("event: error\ndata: {}\n\n", data)
+    }
+
+    /// The envelope as one `{"error": {...}}` line of an NDJSON stream, for
+    /// bulk endpoints that interleave failures with results.
+    pub fn to_ndjson_line(&self) -> String {
+        let line = serde_json::to_string(&serde_json::json!({ "error": self.envelope() }))
+            .unwrap_or_else(|_| r#"{"error":{"message":"internal server error"}}"#.into());
+        format!("{}\n", line)
+    }
+
+    /// The message shown to clients: 5xx details are hidden in production.
+    pub(crate) fn public_message(&self) -> String {
+        let status = error::ResponseError::status_code(self);
+        if status.is_server_error() && observability::production_mode() {
+            "internal server error".into()
+        } else if status.is_client_error()
+            && observability::client_detail_policy() == ClientDetailPolicy::Generic
+        {
+            status
+                .canonical_reason()
+                .unwrap_or("client error")
+                .to_lowercase()
+        } else {
+            self.error_response()
+        }
+    }
+
+    /// The messages of the `source()` chain, outermost first.
+    pub(crate) fn causes(&self) -> Option<Vec<String>> {
+        let mut causes = Vec::new();
+        let mut source = std::error::Error::source(self);
+        while let Some(err) = source {
+            causes.push(err.to_string());
+            source = err.source();
+        }
+        (!causes.is_empty()).then_some(causes)
+    }
+
     fn error_response(&self) -> String {
         match self {
             AppError::Unauthorized => "unauthorized".into(),
@@ -160,7 +1213,45 @@ impl AppError {
             AppError::GrpcConnectionError(error) => error.to_string(),
             AppError::GrpcStatusError(error) => error.to_string(),
             AppError::GraphQLError(error) => error.clone().message,
-            AppError::Mongo(error) => error.to_string()
+            AppError::Mongo(error) => error.to_string(),
+            AppError::BadRequest(msg) => msg.into(),
+            AppError::UnprocessableEntity(msg) => msg.into(),
+            AppError::NotFound(msg) => msg.into(),
+            AppError::Validation(_) => "validation failed".into(),
+            AppError::Timeout(msg) => msg.into(),
+            AppError::NotImplemented { feature: None } => "not implemented".into(),
+            AppError::NotImplemented {
+                feature: Some(feature),
+            } => format!("not implemented: {}", feature),
+            AppError::Degraded(_) => "service degraded".into(),
+            AppError::ServiceUnavailable(msg) => msg.into(),
+            AppError::ResourceBusy(resource) => format!("{} is busy, retry shortly", resource),
+            AppError::NormalizedUpstream(_) => "bad gateway".into(),
+            AppError::NotModified => "not modified".into(),
+            AppError::Warnings(_) => "completed with warnings".into(),
+            AppError::OutOfRange(msg) => msg.into(),
+            AppError::RateLimited(_) => "too many requests".into(),
+            AppError::Decorated(_, error) => error.error_response(),
+            AppError::Shared(error) => error.error_response(),
+            AppError::Custom { message, .. } => message.into(),
+            AppError::Problem(problem) => problem.detail.clone(),
+            AppError::Upstream {
+                service, message, ..
+            } => format!("{}: {}", service, message),
+            #[cfg(feature = "rusqlite")]
+            AppError::Database(err) => err.to_string(),
+            #[cfg(feature = "object_store")]
+            AppError::ObjectStore(err) => err.to_string(),
+            #[cfg(feature = "sea-orm")]
+            AppError::SeaOrm(err) => err.to_string(),
+            #[cfg(feature = "hyper")]
+            AppError::Hyper(err) => err.to_string(),
+            #[cfg(feature = "rustls")]
+            AppError::Tls(err) => err.to_string(),
+            #[cfg(feature = "redis")]
+            AppError::Redis(err) => err.to_string(),
+            #[cfg(feature = "async-nats")]
+            AppError::Nats(err) => err.to_string(),
         }
     }
 }
@@ -171,15 +1262,190 @@ impl From<Error> for AppError {
     }
 }
 
+impl From<MongoError> for AppError {
+    fn from(err: MongoError) -> Self {
+        match err.kind.as_ref() {
+            // `GridFs` is a `#[non_exhaustive]` tuple variant, which can only
+            // be matched from outside the driver with the braced form
+            MongoErrorKind::GridFs {
+                0: GridFsErrorKind::FileNotFound { .. } | GridFsErrorKind::RevisionNotFound { .. },
+                ..
+            } => AppError::NotFound(format!("gridfs: {}", err)),
+            _ => AppError::Mongo(err),
+        }
+    }
+}
+
+/// Caches such as moka hand a failed loader's error to every waiter behind an
+/// `Arc`; it is unwrapped when this is the last reference and otherwise kept
+/// shared, so the original status, code and message survive either way.
+impl From<Arc<AppError>> for AppError {
+    fn from(err: Arc<AppError>) -> Self {
+        Arc::try_unwrap(err).unwrap_or_else(AppError::Shared)
+    }
+}
+
+impl From<ObjectIdError> for AppError {
+    fn from(_: ObjectIdError) -> Self {
+        // the parser's message is not useful to the caller
+        AppError::BadRequest("invalid id".into())
+    }
+}
+
 impl From<ValidationError> for AppError {
     fn from(err: ValidationError) -> Self {
         AppError::JwksError(err)
     }
 }
 
-#[derive(Debug, Serialize)]
+impl From<NulError> for AppError {
+    fn from(err: NulError) -> Self {
+        AppError::BadRequest(format!("invalid string: {}", err))
+    }
+}
+
+impl From<IntoStringError> for AppError {
+    fn from(err: IntoStringError) -> Self {
+        AppError::ServerError(HttpError {
+            status: 500,
+            message: format!("invalid c string: {}", err),
+        })
+    }
+}
+
+impl From<JoinPathsError> for AppError {
+    fn from(err: JoinPathsError) -> Self {
+        AppError::ServerError(HttpError {
+            status: 500,
+            message: format!("could not join paths: {}", err),
+        })
+    }
+}
+
+impl From<StripPrefixError> for AppError {
+    fn from(err: StripPrefixError) -> Self {
+        AppError::ServerError(HttpError {
+            status: 500,
+            message: format!("path does not start with the expected prefix: {}", err),
+        })
+    }
+}
+
+impl From<InvalidMetadataValue> for AppError {
+    fn from(err: InvalidMetadataValue) -> Self {
+        AppError::ServerError(HttpError {
+            status: 500,
+            message: format!("invalid grpc metadata value: {}", err),
+        })
+    }
+}
+
+impl From<InvalidMetadataKey> for AppError {
+    fn from(err: InvalidMetadataKey) -> Self {
+        AppError::ServerError(HttpError {
+            status: 500,
+            message: format!("invalid grpc metadata key: {}", err),
+        })
+    }
+}
+
+impl From<IoError> for AppError {
+    fn from(err: IoError) -> Self {
+        match err.kind() {
+            // the client hung up mid-body, e.g. a truncated upload
+            IoErrorKind::UnexpectedEof => {
+                AppError::BadRequest(format!("incomplete upload: {}", err))
+            }
+            _ => AppError::ServerError(HttpError {
+                status: 500,
+                message: format!("io error: {}", err),
+            }),
+        }
+    }
+}
+
+impl From<BorrowError> for AppError {
+    fn from(err: BorrowError) -> Self {
+        AppError::ServerError(HttpError {
+            status: 500,
+            message: format!("internal state conflict: {}", err),
+        })
+    }
+}
+
+impl From<BorrowMutError> for AppError {
+    fn from(err: BorrowMutError) -> Self {
+        AppError::ServerError(HttpError {
+            status: 500,
+            message: format!("internal state conflict: {}", err),
+        })
+    }
+}
+
+impl From<ToStrError> for AppError {
+    fn from(err: ToStrError) -> Self {
+        AppError::ServerError(HttpError {
+            status: 500,
+            message: format!("header is not valid text: {}", err),
+        })
+    }
+}
+
+impl From<InvalidHeaderValue> for AppError {
+    fn from(err: InvalidHeaderValue) -> Self {
+        AppError::ServerError(HttpError {
+            status: 500,
+            message: format!("invalid header value: {}", err),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AppErrorResponse {
     message: String,
+    code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    components: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errors: Option<Vec<FieldError>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warnings: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_url: Option<String>,
+    /// Development only; never populated in production mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    causes: Option<Vec<String>>,
+}
+
+/// A problem with one input field, listed under `errors` in the envelope.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// The extras attached through [`AppError::with_error_id`],
+/// [`AppError::with_field`], [`AppError::with_details`],
+/// [`AppError::with_context`], [`AppError::with_duration`] and
+/// [`AppError::with_retry_after`].
+#[derive(Debug, Default)]
+pub struct Decorations {
+    id: OnceLock<String>,
+    fields: Vec<FieldError>,
+    details: Option<serde_json::Value>,
+    context: Vec<(String, String)>,
+    duration: Option<Duration>,
+    retry_after: Option<Duration>,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -193,3 +1459,629 @@ impl std::fmt::Display for HttpError {
         write!(f, "api error: {}", self.message)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use actix_web::body::MessageBody;
+    use actix_web::ResponseError;
+    use tonic::metadata::{Ascii, MetadataKey, MetadataValue};
+
+    use super::*;
+
+    // the inherent `error_response` (the message) shadows the trait method
+    fn respond(err: &AppError) -> HttpResponse {
+        ResponseError::error_response(err)
+    }
+
+    fn body_json(response: HttpResponse) -> serde_json::Value {
+        serde_json::from_slice(&response.into_body().try_into_bytes().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn interior_nul_is_a_bad_request() {
+        let err = AppError::from(CString::new("a\0b").unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn non_utf8_c_string_is_a_500() {
+        let err = AppError::from(CString::new(vec![0xff]).unwrap().into_string().unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn degraded_lists_the_unhealthy_components() {
+        let response = respond(&AppError::degraded(vec!["mongo".into(), "redis".into()]));
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            body_json(response)["components"],
+            serde_json::json!(["mongo", "redis"])
+        );
+    }
+
+    #[test]
+    fn upstream_500_normalizes_to_a_502() {
+        let err = AppError::ServerError(HttpError {
+            status: 500,
+            message: "inventory service crashed".into(),
+        })
+        .normalize_upstream();
+        assert_eq!(err.status_code(), StatusCode::BAD_GATEWAY);
+        assert!(err.causes().unwrap()[0].contains("inventory service crashed"));
+    }
+
+    #[test]
+    fn wrapped_upstream_500_normalizes_to_a_502() {
+        let err = AppError::ServerError(HttpError {
+            status: 500,
+            message: "inventory service crashed".into(),
+        })
+        .with_error_id()
+        .with_context("sku", "A-1")
+        .normalize_upstream();
+        assert_eq!(err.status_code(), StatusCode::BAD_GATEWAY);
+        assert!(matches!(err.unwrapped(), AppError::NormalizedUpstream(_)));
+        assert!(err.error_id().is_some());
+    }
+
+    #[test]
+    fn not_modified_has_an_empty_body() {
+        let response = respond(&AppError::not_modified());
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(response.into_body().try_into_bytes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn context_correlation_id_reaches_grpc_metadata() {
+        let status = AppError::NotFound("order 42".into())
+            .with_context("correlation_id", "req-7f3a")
+            .convert_app_error_to_grpc_status();
+        assert_eq!(
+            status.metadata().get("x-correlation-id").unwrap(),
+            "req-7f3a"
+        );
+        assert!(AppError::NotFound("order 42".into())
+            .convert_app_error_to_grpc_status()
+            .metadata()
+            .get("x-correlation-id")
+            .is_none());
+    }
+
+    #[test]
+    fn explicit_correlation_id_overrides_the_context() {
+        let status = AppError::NotFound("order 42".into())
+            .with_context("correlation_id", "req-0000")
+            .convert_app_error_to_grpc_status_with_correlation_id("req-7f3a");
+        assert_eq!(
+            status.metadata().get("x-correlation-id").unwrap(),
+            "req-7f3a"
+        );
+    }
+
+    #[test]
+    fn invalid_metadata_is_a_500() {
+        let err = AppError::from(MetadataValue::try_from("line\nbreak").unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let err = AppError::from(MetadataKey::<Ascii>::from_bytes(b"bad key").unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[cfg(feature = "otel")]
+    #[test]
+    fn traceparent_is_echoed_within_an_active_span() {
+        use opentelemetry::trace::{
+            SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+        };
+
+        let err = AppError::NotFound("order 42".into());
+        assert!(respond(&err).headers().get("traceparent").is_none());
+
+        let span_context = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        let _guard = opentelemetry::Context::current()
+            .with_remote_span_context(span_context)
+            .attach();
+        assert_eq!(
+            respond(&err).headers().get("traceparent").unwrap(),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+        );
+    }
+
+    #[test]
+    fn upstream_codes_survive_the_gateway() {
+        let remote: AppErrorResponse = serde_json::from_value(serde_json::json!({
+            "message": "no such card",
+            "code": "CARD_NOT_FOUND",
+        }))
+        .unwrap();
+        let err = AppError::from_upstream_response("payments", 404, remote);
+
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(err.envelope().code, "CARD_NOT_FOUND");
+        assert_eq!(
+            err.convert_app_error_to_grpc_status().code(),
+            Code::NotFound
+        );
+    }
+
+    #[test]
+    fn header_errors_are_500_unless_the_client_sent_them() {
+        let err = AppError::from(reqwest::header::HeaderValue::from_str("a\nb").unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let value = reqwest::header::HeaderValue::from_bytes(b"caf\xc3\xa9").unwrap();
+        let err = AppError::from(value.to_str().unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let err = AppError::invalid_client_header("x-tenant", value.to_str().unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert!(err.to_string().contains("x-tenant"));
+    }
+
+    #[actix_web::test]
+    async fn grpc_connect_error_is_a_503() {
+        let err = tonic::transport::Endpoint::from_static("http://127.0.0.1:1")
+            .connect()
+            .await
+            .unwrap_err();
+        let err = AppError::GrpcConnectionError(err);
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            err.convert_app_error_to_grpc_status().code(),
+            Code::Unavailable
+        );
+    }
+
+    #[test]
+    fn grpc_tls_config_error_is_a_500() {
+        use tonic::transport::{ClientTlsConfig, Endpoint, Identity};
+
+        let tls = ClientTlsConfig::new().identity(Identity::from_pem("not a cert", "not a key"));
+        let err = Endpoint::from_static("https://127.0.0.1:1")
+            .tls_config(tls)
+            .unwrap_err();
+        let err = AppError::GrpcConnectionError(err);
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            err.convert_app_error_to_grpc_status().code(),
+            Code::Internal
+        );
+    }
+
+    // the driver's GridFS errors cannot be built outside it, so this one
+    // needs a server: `MONGODB_URI=... cargo test -- --ignored`
+    #[actix_web::test]
+    #[ignore]
+    async fn gridfs_file_not_found_is_a_404() {
+        let uri = std::env::var("MONGODB_URI").unwrap_or("mongodb://localhost:27017".into());
+        let client = mongodb::Client::with_uri_str(uri).await.unwrap();
+        let bucket = client.database("error_util_test").gridfs_bucket(None);
+        let Err(err) = bucket
+            .open_download_stream_by_name("missing.png", None)
+            .await
+        else {
+            panic!("expected missing.png to be missing");
+        };
+        let err = AppError::from(err);
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn sse_event_carries_the_envelope() {
+        let err = AppError::NotFound("order 42".into());
+        let frame = err.to_sse_event();
+        let data = frame
+            .strip_prefix("event: error\ndata: ")
+            .and_then(|rest| rest.strip_suffix("\n\n"))
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(data).unwrap(),
+            serde_json::to_value(err.envelope()).unwrap()
+        );
+    }
+
+    #[test]
+    fn ndjson_line_carries_the_envelope() {
+        let err = AppError::NotFound("order 42".into()).with_error_id();
+        let line = err.to_ndjson_line();
+        let json = line.strip_suffix('\n').unwrap();
+        assert!(!json.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(json).unwrap()["error"],
+            serde_json::to_value(err.envelope()).unwrap()
+        );
+    }
+
+    #[test]
+    fn not_implemented_is_a_501() {
+        let err = AppError::not_implemented(Some("export".into()));
+        assert_eq!(err.status_code(), StatusCode::NOT_IMPLEMENTED);
+        assert_eq!(err.to_string(), "not implemented: export");
+        assert_eq!(
+            err.convert_app_error_to_grpc_status().code(),
+            Code::Unimplemented
+        );
+    }
+
+    #[test]
+    fn resource_busy_is_a_503_with_a_retry_hint() {
+        let response = respond(&AppError::resource_busy("ledger lock"));
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers().get("Retry-After").unwrap(), "1");
+    }
+
+    #[actix_web::test]
+    async fn head_request_gets_the_status_without_a_body() {
+        use actix_web::{test, web, App};
+
+        let app = test::init_service(App::new().default_service(web::to(
+            |req: HttpRequest| async move {
+                AppError::NotFound("order 42".into()).error_response_with_request(&req)
+            },
+        )))
+        .await;
+        let request = test::TestRequest::default()
+            .method(Method::HEAD)
+            .uri("/orders/42")
+            .to_request();
+        let response = test::call_service(&app, request).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(test::read_body(response).await.is_empty());
+    }
+
+    #[actix_web::test]
+    async fn prefer_minimal_gets_the_status_without_a_body() {
+        use actix_web::{test, web, App};
+
+        let app = test::init_service(App::new().default_service(web::to(
+            |req: HttpRequest| async move {
+                AppError::NotFound("order 42".into()).error_response_with_request(&req)
+            },
+        )))
+        .await;
+        let request = test::TestRequest::get()
+            .uri("/orders/42")
+            .insert_header(("Prefer", "respond-async, return=minimal"))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            response.headers().get("preference-applied").unwrap(),
+            "return=minimal"
+        );
+        assert!(test::read_body(response).await.is_empty());
+
+        let request = test::TestRequest::get().uri("/orders/42").to_request();
+        let response = test::call_service(&app, request).await;
+        assert!(response.headers().get("preference-applied").is_none());
+        assert!(!test::read_body(response).await.is_empty());
+    }
+
+    #[test]
+    fn merge_keeps_the_more_severe_error_and_both_messages() {
+        let merged = AppError::NotFound("user 7".into()).merge(AppError::custom(
+            500,
+            "LEDGER_DOWN",
+            "ledger unreachable",
+        ));
+
+        assert_eq!(merged.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(merged.code(), "LEDGER_DOWN");
+        assert_eq!(
+            body_json(respond(&merged))["details"]["messages"],
+            serde_json::json!(["ledger unreachable", "user 7"])
+        );
+
+        let merged = merged.merge(AppError::BadRequest("bad page size".into()));
+        assert_eq!(
+            merged.envelope().details.unwrap()["messages"],
+            serde_json::json!(["ledger unreachable", "user 7", "bad page size"])
+        );
+    }
+
+    #[test]
+    fn duration_is_sent_as_server_timing() {
+        let err = AppError::Timeout("ledger".into())
+            .with_duration(Duration::from_millis(250))
+            .with_error_id()
+            .with_duration(Duration::from_millis(1234));
+        let response = respond(&err);
+        assert_eq!(
+            response.headers().get("Server-Timing").unwrap(),
+            "app;dur=1234"
+        );
+        assert!(respond(&AppError::Timeout("ledger".into()))
+            .headers()
+            .get("Server-Timing")
+            .is_none());
+    }
+
+    #[actix_web::test]
+    async fn truncated_async_upload_is_a_bad_request() {
+        use tokio::io::AsyncReadExt;
+
+        let mut body: &[u8] = b"\x00\x00";
+        let err = AppError::from(body.read_u32().await.unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert!(err.error_response().starts_with("incomplete upload"));
+
+        let err = AppError::from(IoError::other("disk full"));
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn merge_keeps_the_winners_own_details() {
+        let winner = AppError::custom(503, "KAFKA_DELIVERY_FAILED", "delivery failed")
+            .with_details(serde_json::json!({ "topic": "orders", "partition": 3 }));
+        let merged = winner.merge(AppError::NotFound("user 7".into()));
+
+        let details = merged.envelope().details.unwrap();
+        assert_eq!(details["topic"], "orders");
+        assert_eq!(details["partition"], 3);
+        assert_eq!(
+            details["messages"],
+            serde_json::json!(["delivery failed", "user 7"])
+        );
+    }
+
+    #[test]
+    fn loader_error_propagates_through_the_cache_arc() {
+        let sole = AppError::from(Arc::new(AppError::NotFound("user 42".into())));
+        assert!(matches!(sole, AppError::NotFound(ref id) if id == "user 42"));
+
+        let loaded = Arc::new(AppError::NotFound("user 42".into()));
+        let _other_waiter = Arc::clone(&loaded);
+        let shared = AppError::from(loaded);
+        assert_eq!(shared.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(shared.code(), "NOT_FOUND");
+        assert_eq!(shared.to_string(), "not found: user 42");
+    }
+
+    #[test]
+    fn cache_miss_is_a_404() {
+        assert_eq!(
+            AppError::cache_miss("user:42").status_code(),
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn join_paths_error_is_a_500() {
+        let err = AppError::from(std::env::join_paths(["/usr/bin", "/opt/a:b"]).unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn strip_prefix_error_is_a_500() {
+        let err = AppError::from(
+            std::path::Path::new("/var/data")
+                .strip_prefix("/srv")
+                .unwrap_err(),
+        );
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn grpc_status_carries_the_http_status() {
+        let status = AppError::NotFound("order 42".into()).convert_app_error_to_grpc_status();
+        assert_eq!(status.metadata().get("x-http-status").unwrap(), "404");
+    }
+
+    #[test]
+    fn warnings_are_a_200_with_a_warnings_body() {
+        let response = respond(&AppError::warnings(vec!["nickname was truncated".into()]));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            body_json(response)["warnings"],
+            serde_json::json!(["nickname was truncated"])
+        );
+    }
+
+    // the driver only lets labels in from a server reply, so build one
+    fn labelled_mongo_error(code: i32, code_name: &str, labels: &[&str]) -> MongoError {
+        use mongodb::error::{ErrorKind as MongoErrorKind, WriteConcernError, WriteFailure};
+
+        let reply = bson::doc! {
+            "code": code,
+            "codeName": code_name,
+            "errmsg": "change stream failed",
+            "errorLabels": labels,
+        };
+        let concern: WriteConcernError = bson::from_document(reply).unwrap();
+        MongoErrorKind::Write(WriteFailure::WriteConcernError(concern)).into()
+    }
+
+    #[test]
+    fn transient_transaction_error_is_a_retryable_503() {
+        let err = AppError::from(labelled_mongo_error(
+            112,
+            "WriteConflict",
+            &["TransientTransactionError"],
+        ));
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn unknown_commit_result_is_a_retryable_503() {
+        let err = AppError::from(labelled_mongo_error(
+            50,
+            "MaxTimeMSExpired",
+            &["UnknownTransactionCommitResult"],
+        ));
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn resumable_change_stream_error_is_a_retryable_503() {
+        let err = AppError::from(labelled_mongo_error(
+            91,
+            "ShutdownInProgress",
+            &["ResumableChangeStreamError"],
+        ));
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn fatal_change_stream_error_is_a_500() {
+        let err = AppError::from(labelled_mongo_error(
+            280,
+            "ChangeStreamFatalError",
+            &["NonResumableChangeStreamError"],
+        ));
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!err.is_retryable());
+    }
+
+    // a write conflict between two transactions, which the server labels
+    // `TransientTransactionError`; needs a replica set:
+    // `MONGODB_URI=... cargo test -- --ignored`
+    #[actix_web::test]
+    #[ignore]
+    async fn server_write_conflict_is_a_retryable_503() {
+        use mongodb::bson::doc;
+
+        let uri = std::env::var("MONGODB_URI").unwrap_or("mongodb://localhost:27017".into());
+        let client = mongodb::Client::with_uri_str(uri).await.unwrap();
+        let accounts = client
+            .database("error_util_test")
+            .collection::<mongodb::bson::Document>("accounts");
+        accounts
+            .update_one(
+                doc! { "_id": 1 },
+                doc! { "$set": { "balance": 0 } },
+                mongodb::options::UpdateOptions::builder()
+                    .upsert(true)
+                    .build(),
+            )
+            .await
+            .unwrap();
+
+        let mut first = client.start_session(None).await.unwrap();
+        let mut second = client.start_session(None).await.unwrap();
+        first.start_transaction(None).await.unwrap();
+        second.start_transaction(None).await.unwrap();
+        accounts
+            .update_one_with_session(
+                doc! { "_id": 1 },
+                doc! { "$inc": { "balance": 1 } },
+                None,
+                &mut first,
+            )
+            .await
+            .unwrap();
+        let err = accounts
+            .update_one_with_session(
+                doc! { "_id": 1 },
+                doc! { "$inc": { "balance": 1 } },
+                None,
+                &mut second,
+            )
+            .await
+            .unwrap_err();
+
+        let err = AppError::from(err);
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn timeout_terminates_a_stream_with_deadline_exceeded() {
+        let stream: Vec<Result<u32, Status>> = vec![
+            Ok(1),
+            Ok(2),
+            Err(AppError::Timeout("export timed out".into()).into_stream_status()),
+        ];
+        let last = stream.into_iter().last().unwrap().unwrap_err();
+        assert_eq!(last.code(), Code::DeadlineExceeded);
+    }
+
+    #[test]
+    fn dual_response_shares_the_code() {
+        let err = AppError::custom(409, "DUPLICATE_EMAIL", "email already registered");
+        let (http, grpc) = err.dual_response();
+
+        assert_eq!(http.status(), StatusCode::CONFLICT);
+        let body = body_json(http);
+        assert_eq!(body["code"], "DUPLICATE_EMAIL");
+        assert_eq!(
+            grpc.metadata().get("x-error-code").unwrap(),
+            "DUPLICATE_EMAIL"
+        );
+        assert_eq!(grpc.message(), body["message"]);
+    }
+
+    #[test]
+    fn graphql_source_and_extensions_survive_wrapping() {
+        use async_graphql::ErrorExtensions;
+
+        let original = GraphQLError::new_with_source(IoError::other("resolver failed"))
+            .extend_with(|_, extensions| extensions.set("path", "user.orders"));
+        let err = AppError::GraphQLError(original).with_error_id();
+
+        let converted = err.convert_app_error_to_graphql_error();
+        assert!(converted.source.is_some());
+        let extensions = serde_json::to_value(converted.extensions).unwrap();
+        assert_eq!(extensions["path"], "user.orders");
+    }
+
+    #[test]
+    fn malformed_object_id_is_a_bad_request() {
+        let err = AppError::from(bson::oid::ObjectId::parse_str("not-an-object-id").unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(err.to_string(), "bad request: invalid id");
+    }
+
+    #[test]
+    fn conflict_with_a_field_lists_it_in_the_body() {
+        let err = AppError::custom(409, "CONFLICT", "already registered")
+            .with_field("email", "email already taken");
+        let response = respond(&err);
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(
+            body_json(response)["errors"],
+            serde_json::json!([{ "field": "email", "message": "email already taken" }])
+        );
+    }
+
+    #[actix_web::test]
+    async fn undecodable_upstream_body_is_a_502() {
+        let upstream = reqwest::Response::from(http::Response::new("<html>oops</html>"));
+        let err = upstream.json::<serde_json::Value>().await.unwrap_err();
+        assert!(err.is_decode());
+        assert_eq!(
+            AppError::ReqwestAPIError(err).status_code(),
+            StatusCode::BAD_GATEWAY
+        );
+    }
+
+    #[test]
+    fn shared_borrow_conflict_is_an_internal_error() {
+        let cell = std::cell::RefCell::new(0);
+        let _guard = cell.borrow_mut();
+        let err = AppError::from(cell.try_borrow().unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(err.error_response().starts_with("internal state conflict"));
+    }
+
+    #[test]
+    fn mutable_borrow_conflict_is_an_internal_error() {
+        let cell = std::cell::RefCell::new(0);
+        let _guard = cell.borrow();
+        let err = AppError::from(cell.try_borrow_mut().unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(err.error_response().starts_with("internal state conflict"));
+    }
+}
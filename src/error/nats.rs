@@ -0,0 +1,76 @@
+use actix_web::http::StatusCode;
+use async_nats::client::PublishErrorKind;
+use async_nats::{ConnectError, Error as NatsError, PublishError, RequestError, RequestErrorKind};
+
+use super::AppError;
+
+/// 503 when nobody answered or the connection is gone, 504 when a request
+/// timed out, 500 for anything that is our own fault.
+pub(super) fn status_code(err: &NatsError) -> StatusCode {
+    if let Some(err) = err.downcast_ref::<RequestError>() {
+        return match err.kind() {
+            RequestErrorKind::NoResponders => StatusCode::SERVICE_UNAVAILABLE,
+            RequestErrorKind::TimedOut => StatusCode::GATEWAY_TIMEOUT,
+            RequestErrorKind::Other => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+    }
+    if let Some(err) = err.downcast_ref::<PublishError>() {
+        return match err.kind() {
+            PublishErrorKind::Send => StatusCode::SERVICE_UNAVAILABLE,
+            PublishErrorKind::MaxPayloadExceeded => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+    }
+    if err.is::<ConnectError>() {
+        return StatusCode::SERVICE_UNAVAILABLE;
+    }
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+impl From<RequestError> for AppError {
+    fn from(err: RequestError) -> Self {
+        AppError::Nats(Box::new(err))
+    }
+}
+
+impl From<PublishError> for AppError {
+    fn from(err: PublishError) -> Self {
+        AppError::Nats(Box::new(err))
+    }
+}
+
+impl From<ConnectError> for AppError {
+    fn from(err: ConnectError) -> Self {
+        AppError::Nats(Box::new(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use tonic::Code;
+
+    use super::*;
+
+    #[test]
+    fn no_responders_is_a_503() {
+        let err = AppError::from(RequestError::from(RequestErrorKind::NoResponders));
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            err.convert_app_error_to_grpc_status().code(),
+            Code::Unavailable
+        );
+        assert_eq!(err.to_string(), "nats error: no responders");
+    }
+
+    #[test]
+    fn request_timeout_is_a_504() {
+        let err = AppError::from(RequestError::from(RequestErrorKind::TimedOut));
+        assert_eq!(err.status_code(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(
+            err.convert_app_error_to_grpc_status().code(),
+            Code::DeadlineExceeded
+        );
+        assert_eq!(err.to_string(), "nats error: request timed out");
+    }
+}
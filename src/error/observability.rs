@@ -0,0 +1,334 @@
+use std::sync::{PoisonError, RwLock};
+
+use super::AppError;
+
+/// Process-wide settings for the cross-cutting behaviour of [`AppError`],
+/// applied once at startup through [`AppError::init`].
+#[derive(Debug, Clone, Default)]
+pub struct ObservabilityConfig {
+    /// Emit a tracing event whenever an error is turned into a response.
+    /// Requires the `tracing` feature.
+    pub tracing: bool,
+    /// Count every error response in the `app_errors_total` counter.
+    /// Requires the `metrics` feature.
+    pub metrics: bool,
+    /// Report every 5xx to the current Sentry hub. Requires the `sentry`
+    /// feature.
+    pub sentry: bool,
+    /// Replace the message of 5xx responses with a generic one.
+    pub production_mode: bool,
+    /// Attached to every emitted event as `service.name`.
+    pub service_name: String,
+}
+
+static CONFIG: RwLock<ObservabilityConfig> = RwLock::new(ObservabilityConfig {
+    tracing: false,
+    metrics: false,
+    sentry: false,
+    production_mode: false,
+    service_name: String::new(),
+});
+
+/// Decides whether a given error gets logged; `false` drops the event.
+pub type LogSampler = Box<dyn Fn(&AppError) -> bool + Send + Sync>;
+
+static LOG_SAMPLER: RwLock<Option<LogSampler>> = RwLock::new(None);
+
+/// How much of a 4xx message reaches the client. 5xx redaction is governed
+/// separately by [`ObservabilityConfig::production_mode`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClientDetailPolicy {
+    /// Send the specific message, e.g. "user 123 not found".
+    #[default]
+    Full,
+    /// Send only the status phrase, e.g. "not found"; the code is kept.
+    Generic,
+}
+
+static CLIENT_DETAIL_POLICY: RwLock<ClientDetailPolicy> = RwLock::new(ClientDetailPolicy::Full);
+
+impl AppError {
+    /// Configures every observability hook in one call. Meant to be called
+    /// once, before the first request is served, and after the tracing
+    /// subscriber and Sentry client it reports to are set up: an integration
+    /// that is requested but not compiled in or not initialized is an error.
+    #[allow(clippy::result_large_err)]
+    pub fn init(config: ObservabilityConfig) -> Result<(), AppError> {
+        let integrations = [
+            ("tracing", config.tracing, cfg!(feature = "tracing")),
+            ("metrics", config.metrics, cfg!(feature = "metrics")),
+            ("sentry", config.sentry, cfg!(feature = "sentry")),
+        ];
+        for (name, requested, available) in integrations {
+            if requested && !available {
+                return Err(AppError::StandardError(format!(
+                    "{} was requested but the `{}` feature is not enabled",
+                    name, name
+                )));
+            }
+        }
+
+        // compiled in, but events would go nowhere
+        #[cfg(feature = "tracing")]
+        if config.tracing && !tracing::dispatcher::has_been_set() {
+            return Err(AppError::StandardError(
+                "tracing was requested but no subscriber is installed".into(),
+            ));
+        }
+        #[cfg(feature = "sentry")]
+        if config.sentry && sentry_core::Hub::current().client().is_none() {
+            return Err(AppError::StandardError(
+                "sentry was requested but no client is bound to the current hub".into(),
+            ));
+        }
+
+        *CONFIG.write().unwrap_or_else(PoisonError::into_inner) = config;
+        Ok(())
+    }
+
+    /// Installs a sampler consulted before every log emission, e.g. to drop
+    /// most repetitive `NotFound`s while still logging every 5xx. Logging
+    /// needs the `tracing` feature, so without it the sampler is never
+    /// called; metrics and Sentry reports are not sampled.
+    pub fn set_log_sampler(sampler: LogSampler) {
+        *LOG_SAMPLER.write().unwrap_or_else(PoisonError::into_inner) = Some(sampler);
+    }
+
+    /// Genericizes 4xx messages on public deployments to avoid leaking what
+    /// exists, e.g. through "user 123 not found".
+    pub fn set_client_detail_policy(policy: ClientDetailPolicy) {
+        *CLIENT_DETAIL_POLICY
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = policy;
+    }
+
+    /// The fields the crate records on its own events, for callers that
+    /// would rather `span.record` them on a span of their own.
+    pub fn tracing_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("error.kind", self.kind().to_string()),
+            ("error.code", self.code()),
+            (
+                "error.status",
+                actix_web::ResponseError::status_code(self)
+                    .as_u16()
+                    .to_string(),
+            ),
+            ("error.category", self.category().to_string()),
+        ]
+    }
+
+    /// A single structured log line. Logs are internal, so the message is
+    /// never redacted, even in production mode.
+    pub fn to_log_json(&self) -> serde_json::Value {
+        let status = actix_web::ResponseError::status_code(self).as_u16();
+        let mut context = serde_json::Map::new();
+        context.insert("service.name".into(), service_name().into());
+        if let Some(causes) = self.causes() {
+            context.insert("causes".into(), causes.into());
+        }
+        for (key, value) in self.context() {
+            context.insert(key, value.into());
+        }
+
+        serde_json::json!({
+            "level": if status >= 500 { "error" } else { "warn" },
+            "error_kind": self.kind(),
+            "code": self.code(),
+            "status": status,
+            "message": self.to_string(),
+            "category": self.category(),
+            "context": context,
+        })
+    }
+
+    /// Everything that happens when the error is turned into a response:
+    /// the log event, the metric and the Sentry report, as configured.
+    pub(crate) fn report(&self, id: &str) {
+        self.log_with_id(Some(id));
+
+        #[cfg(feature = "metrics")]
+        if metrics_enabled() {
+            let status = actix_web::ResponseError::status_code(self).as_u16();
+            metrics::counter!(
+                "app_errors_total",
+                "kind" => self.kind(),
+                "code" => self.code(),
+                "status" => status.to_string(),
+            )
+            .increment(1);
+        }
+
+        #[cfg(feature = "sentry")]
+        if sentry_enabled() && actix_web::ResponseError::status_code(self).is_server_error() {
+            sentry_core::capture_error(self);
+        }
+    }
+
+    pub(crate) fn log(&self) {
+        self.log_with_id(self.error_id());
+    }
+
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    fn log_with_id(&self, id: Option<&str>) {
+        #[cfg(feature = "tracing")]
+        if tracing_enabled() && sampled(self) {
+            let status = actix_web::ResponseError::status_code(self).as_u16();
+            let service = service_name();
+            let (kind, code, category) = (self.kind(), self.code(), self.category());
+            let context = self
+                .context()
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let context = (!context.is_empty()).then_some(context);
+            if status >= 500 {
+                tracing::error!(
+                    service.name = %service,
+                    error.kind = kind,
+                    error.code = %code,
+                    error.status = status,
+                    error.category = category,
+                    error.id = id,
+                    error.context = context.as_deref(),
+                    "{}",
+                    self
+                );
+            } else {
+                tracing::warn!(
+                    service.name = %service,
+                    error.kind = kind,
+                    error.code = %code,
+                    error.status = status,
+                    error.category = category,
+                    error.id = id,
+                    error.context = context.as_deref(),
+                    "{}",
+                    self
+                );
+            }
+        }
+    }
+}
+
+/// W3C `traceparent` of the active OpenTelemetry span, if there is one.
+#[cfg(feature = "otel")]
+pub(crate) fn traceparent() -> Option<String> {
+    use opentelemetry::trace::TraceContextExt;
+
+    let context = opentelemetry::Context::current();
+    let span_context = context.span().span_context().clone();
+    span_context.is_valid().then(|| {
+        format!(
+            "00-{}-{}-{:02x}",
+            span_context.trace_id(),
+            span_context.span_id(),
+            span_context.trace_flags().to_u8()
+        )
+    })
+}
+
+pub(crate) fn client_detail_policy() -> ClientDetailPolicy {
+    *CLIENT_DETAIL_POLICY
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+pub(crate) fn production_mode() -> bool {
+    CONFIG
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .production_mode
+}
+
+#[cfg(feature = "tracing")]
+fn sampled(err: &AppError) -> bool {
+    LOG_SAMPLER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .is_none_or(|sampler| sampler(err))
+}
+
+#[cfg(feature = "metrics")]
+fn metrics_enabled() -> bool {
+    CONFIG
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .metrics
+}
+
+#[cfg(feature = "sentry")]
+fn sentry_enabled() -> bool {
+    CONFIG.read().unwrap_or_else(PoisonError::into_inner).sentry
+}
+
+#[cfg(feature = "tracing")]
+fn tracing_enabled() -> bool {
+    CONFIG
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .tracing
+}
+
+fn service_name() -> String {
+    CONFIG
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .service_name
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use mongodb::error::Error as MongoError;
+
+    use super::*;
+
+    #[test]
+    fn tracing_fields_describe_a_mongo_error() {
+        let err = AppError::Mongo(MongoError::custom("boom"));
+        assert_eq!(
+            err.tracing_fields(),
+            vec![
+                ("error.kind", "Mongo".to_string()),
+                ("error.code", "INTERNAL_SERVER_ERROR".to_string()),
+                ("error.status", "500".to_string()),
+                ("error.category", "server".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn log_json_has_the_documented_keys() {
+        let log = AppError::NotFound("order 42".into()).to_log_json();
+        let mut keys: Vec<_> = log.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "category",
+                "code",
+                "context",
+                "error_kind",
+                "level",
+                "message",
+                "status"
+            ]
+        );
+        assert_eq!(log["level"], "warn");
+        assert_eq!(log["status"], 404);
+    }
+
+    #[test]
+    fn log_json_carries_the_context() {
+        let log = AppError::NotFound("order 42".into())
+            .with_context("order_id", 42)
+            .with_error_id()
+            .with_context("tenant", "acme")
+            .to_log_json();
+        assert_eq!(log["context"]["order_id"], "42");
+        assert_eq!(log["context"]["tenant"], "acme");
+    }
+}
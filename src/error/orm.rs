@@ -0,0 +1,45 @@
+use actix_web::http::StatusCode;
+use sea_orm::{DbErr, SqlErr};
+
+use super::AppError;
+
+pub(super) fn status_code(err: &DbErr) -> StatusCode {
+    match err {
+        DbErr::RecordNotFound(_) => StatusCode::NOT_FOUND,
+        DbErr::Conn(_) | DbErr::ConnectionAcquire(_) => StatusCode::SERVICE_UNAVAILABLE,
+        _ if matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+            StatusCode::CONFLICT
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl From<DbErr> for AppError {
+    fn from(err: DbErr) -> Self {
+        AppError::SeaOrm(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::ResponseError;
+    use sea_orm::{ConnAcquireErr, RuntimeErr};
+
+    use super::*;
+
+    #[test]
+    fn record_not_found_is_a_404() {
+        let err = AppError::from(DbErr::RecordNotFound("user 42".into()));
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+        assert!(err.to_string().starts_with("database error: "));
+    }
+
+    #[test]
+    fn connection_errors_are_503() {
+        let err = AppError::from(DbErr::Conn(RuntimeErr::Internal("refused".into())));
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let err = AppError::from(DbErr::ConnectionAcquire(ConnAcquireErr::Timeout));
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,41 @@
+use password_hash::Error as PasswordHashError;
+
+use super::{AppError, HttpError};
+
+impl From<PasswordHashError> for AppError {
+    fn from(err: PasswordHashError) -> Self {
+        match err {
+            // a verification mismatch must not tell the caller anything more
+            PasswordHashError::Password => {
+                AppError::custom(401, "INVALID_CREDENTIALS", "invalid credentials")
+            }
+            _ => AppError::ServerError(HttpError {
+                status: 500,
+                message: format!("password hash error: {}", err),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use password_hash::PasswordHash;
+
+    use super::*;
+
+    #[test]
+    fn mismatch_is_a_generic_401() {
+        let err = AppError::from(PasswordHashError::Password);
+        assert_eq!(err.status_code(), StatusCode::UNAUTHORIZED);
+        assert_eq!(err.code(), "INVALID_CREDENTIALS");
+        assert_eq!(err.envelope().message, "invalid credentials");
+    }
+
+    #[test]
+    fn malformed_hash_is_a_500() {
+        let err = AppError::from(PasswordHash::new("not a phc string").unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,41 @@
+use bb8::RunError;
+
+use super::AppError;
+
+impl<E> From<RunError<E>> for AppError
+where
+    E: Into<AppError>,
+{
+    fn from(err: RunError<E>) -> Self {
+        match err {
+            // the connection's own error keeps its usual mapping
+            RunError::User(err) => err.into(),
+            RunError::TimedOut => {
+                AppError::ServiceUnavailable("timed out waiting for a pooled connection".into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+
+    use super::*;
+
+    #[test]
+    fn pool_timeout_is_a_503() {
+        let err = AppError::from(RunError::<AppError>::TimedOut);
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[cfg(feature = "redis")]
+    #[test]
+    fn inner_redis_error_keeps_its_mapping() {
+        let inner = redis::RedisError::from((redis::ErrorKind::TypeError, "not a list"));
+        let err = AppError::from(RunError::User(inner));
+        assert!(matches!(err, AppError::Redis(_)));
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
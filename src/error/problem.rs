@@ -0,0 +1,104 @@
+use std::sync::{PoisonError, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use super::AppError;
+
+static BASE_URI: RwLock<String> = RwLock::new(String::new());
+
+/// An RFC 7807 `application/problem+json` document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type", default = "about_blank")]
+    pub type_uri: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub status: u16,
+    #[serde(default)]
+    pub detail: String,
+}
+
+fn about_blank() -> String {
+    "about:blank".into()
+}
+
+impl ProblemDetails {
+    /// The last segment of a dereferenceable `type`, e.g. `NOT_FOUND` for
+    /// `https://errors.example.com/NOT_FOUND`.
+    pub(super) fn code(&self) -> Option<&str> {
+        if self.type_uri == "about:blank" {
+            return None;
+        }
+        self.type_uri
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .filter(|segment| !segment.is_empty())
+    }
+}
+
+impl AppError {
+    /// Base of the `type` URI in problem documents, which then becomes
+    /// `{base}/{code}`. Until set, `type` is `about:blank`.
+    pub fn set_problem_base_uri(base: impl Into<String>) {
+        *BASE_URI.write().unwrap_or_else(PoisonError::into_inner) = base.into();
+    }
+
+    /// Parses an upstream's `application/problem+json` body. A body that is
+    /// not a problem document is kept verbatim as the detail.
+    pub fn from_problem_json(status: u16, body: &[u8]) -> Self {
+        let mut problem = serde_json::from_slice(body).unwrap_or_else(|_| ProblemDetails {
+            type_uri: about_blank(),
+            title: String::new(),
+            status,
+            detail: String::from_utf8_lossy(body).into_owned(),
+        });
+        if problem.status == 0 {
+            problem.status = status;
+        }
+        AppError::Problem(problem)
+    }
+
+    pub fn problem_details(&self) -> ProblemDetails {
+        if let AppError::Problem(problem) = self.unwrapped() {
+            return problem.clone();
+        }
+
+        let status = actix_web::ResponseError::status_code(self);
+        let base = BASE_URI.read().unwrap_or_else(PoisonError::into_inner);
+        let type_uri = if base.is_empty() {
+            "about:blank".to_string()
+        } else {
+            format!("{}/{}", base.trim_end_matches('/'), self.code())
+        };
+
+        ProblemDetails {
+            type_uri,
+            title: status.canonical_reason().unwrap_or("Unknown").to_string(),
+            status: status.as_u16(),
+            detail: self.public_message(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+
+    use super::*;
+
+    #[test]
+    fn produced_problem_round_trips() {
+        let produced = AppError::NotFound("order 42".into()).problem_details();
+        let body = serde_json::to_vec(&produced).unwrap();
+
+        let err = AppError::from_problem_json(404, &body);
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+        let consumed = err.problem_details();
+        assert_eq!(consumed.detail, produced.detail);
+        assert_eq!(consumed.title, produced.title);
+        assert_eq!(consumed.type_uri, produced.type_uri);
+    }
+}
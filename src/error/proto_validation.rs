@@ -0,0 +1,55 @@
+use prost_validate::Error as ProtoValidationError;
+
+use super::{AppError, FieldError};
+
+fn field_error(err: ProtoValidationError) -> FieldError {
+    FieldError {
+        field: err.field,
+        message: err.details.to_string(),
+    }
+}
+
+/// `field` is the proto field path, e.g. `user.email`.
+impl From<ProtoValidationError> for AppError {
+    fn from(err: ProtoValidationError) -> Self {
+        AppError::Validation(vec![field_error(err)])
+    }
+}
+
+/// For callers collecting the violations of several messages before
+/// answering.
+impl From<Vec<ProtoValidationError>> for AppError {
+    fn from(errors: Vec<ProtoValidationError>) -> Self {
+        AppError::Validation(errors.into_iter().map(field_error).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use prost_validate::errors::string;
+    use tonic::Code;
+    use tonic_types::StatusExt;
+
+    use super::*;
+
+    #[test]
+    fn both_violations_reach_the_grpc_details() {
+        let err = AppError::from(vec![
+            ProtoValidationError::new("user.email", string::Error::Email),
+            ProtoValidationError::new("user.name", string::Error::MinLen(1)),
+        ]);
+        assert_eq!(err.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let status = err.convert_app_error_to_grpc_status();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        let violations = status.get_details_bad_request().unwrap().field_violations;
+        let fields: Vec<&str> = violations.iter().map(|v| v.field.as_str()).collect();
+        assert_eq!(fields, ["user.email", "user.name"]);
+        assert_eq!(
+            violations[1].description,
+            "characters length must be greater than or equal to 1"
+        );
+    }
+}
@@ -0,0 +1,37 @@
+use serde_qs::Error as QueryStringError;
+
+use super::AppError;
+
+impl From<QueryStringError> for AppError {
+    fn from(err: QueryStringError) -> Self {
+        AppError::BadRequest(format!("invalid query string: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Search {
+        filter: Filter,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Filter {
+        limit: u32,
+    }
+
+    #[test]
+    fn malformed_nested_query_is_a_bad_request() {
+        let err = AppError::from(serde_qs::from_str::<Search>("filter[limit=1").unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert!(err.to_string().contains("limit"), "{}", err);
+    }
+}
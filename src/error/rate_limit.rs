@@ -0,0 +1,32 @@
+use governor::clock::{Clock, DefaultClock};
+use governor::NotUntil;
+
+use super::AppError;
+
+type Instant = <DefaultClock as Clock>::Instant;
+
+impl From<NotUntil<Instant>> for AppError {
+    fn from(err: NotUntil<Instant>) -> Self {
+        AppError::RateLimited(err.wait_time_from(DefaultClock::default().now()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use governor::{Quota, RateLimiter};
+
+    use super::*;
+
+    #[test]
+    fn rejection_is_a_429_with_a_retry_hint() {
+        let limiter = RateLimiter::direct(Quota::per_hour(NonZeroU32::MIN));
+        limiter.check().unwrap();
+        let err = AppError::from(limiter.check().unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(matches!(err, AppError::RateLimited(wait) if !wait.is_zero()));
+    }
+}
@@ -0,0 +1,16 @@
+use super::AppError;
+
+pub trait ResultExt {
+    /// Emits the log event for an error right here, where the local context
+    /// is still in scope, and hands the result back untouched.
+    fn or_log(self) -> Self;
+}
+
+impl<T> ResultExt for Result<T, AppError> {
+    fn or_log(self) -> Self {
+        if let Err(err) = &self {
+            err.log();
+        }
+        self
+    }
+}
@@ -0,0 +1,37 @@
+use std::sync::{PoisonError, RwLock};
+use std::time::{Duration, SystemTime};
+
+use actix_web::http::header::HttpDate;
+
+use super::AppError;
+
+/// How the `Retry-After` header is rendered; RFC 9110 allows either.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RetryAfterFormat {
+    /// A delay in whole seconds, e.g. `Retry-After: 3`.
+    #[default]
+    Seconds,
+    /// An IMF-fixdate, e.g. `Retry-After: Wed, 21 Oct 2026 07:28:00 GMT`.
+    HttpDate,
+}
+
+static FORMAT: RwLock<RetryAfterFormat> = RwLock::new(RetryAfterFormat::Seconds);
+
+impl AppError {
+    /// Applies to every `Retry-After` sent: rate limits, unavailable
+    /// services, busy resources and explicit retry hints alike.
+    pub fn set_retry_after_format(format: RetryAfterFormat) {
+        *FORMAT.write().unwrap_or_else(PoisonError::into_inner) = format;
+    }
+}
+
+pub(super) fn header_value(delay: Duration) -> String {
+    // whole seconds, rounded up so clients never retry too early
+    let seconds = delay.as_secs() + u64::from(delay.subsec_nanos() > 0);
+    match *FORMAT.read().unwrap_or_else(PoisonError::into_inner) {
+        RetryAfterFormat::Seconds => seconds.to_string(),
+        RetryAfterFormat::HttpDate => {
+            HttpDate::from(SystemTime::now() + Duration::from_secs(seconds)).to_string()
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::sync::{PoisonError, RwLock};
+
+use actix_web::http::StatusCode;
+
+use super::AppError;
+
+struct StandardErrorRule {
+    pattern: String,
+    status: StatusCode,
+    code: String,
+}
+
+static RULES: RwLock<Vec<StandardErrorRule>> = RwLock::new(Vec::new());
+
+impl AppError {
+    /// Migration aid for stringly-typed errors: a `StandardError` whose
+    /// message contains `pattern` is answered with `status` and `code`
+    /// instead of a 500. Rules are tried in registration order.
+    pub fn add_standard_error_rule(
+        pattern: impl Into<String>,
+        status: u16,
+        code: impl Into<String>,
+    ) {
+        RULES
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(StandardErrorRule {
+                pattern: pattern.into(),
+                status: StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                code: code.into(),
+            });
+    }
+}
+
+/// The status and code of the first rule matching `message`, if any.
+pub(super) fn standard_error_rule(message: &str) -> Option<(StatusCode, String)> {
+    RULES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .find(|rule| message.contains(&rule.pattern))
+        .map(|rule| (rule.status, rule.code.clone()))
+}
@@ -0,0 +1,50 @@
+use config::ConfigError as SettingsError;
+
+use super::{AppError, HttpError};
+
+impl From<SettingsError> for AppError {
+    fn from(err: SettingsError) -> Self {
+        let message = match &err {
+            // name the failing file so the operator knows where to look;
+            // the parser's own message carries the line and column
+            SettingsError::FileParse { uri, cause } => format!(
+                "failed to parse {}: {}",
+                uri.as_deref().unwrap_or("configuration source"),
+                cause
+            ),
+            _ => err.to_string(),
+        };
+
+        AppError::ServerError(HttpError {
+            status: 500,
+            message: format!("configuration error: {}", message),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::{Config, File, FileFormat};
+
+    use super::*;
+
+    #[test]
+    fn broken_file_is_named_in_the_message() {
+        let path = std::env::temp_dir().join("error-util-broken-settings.json");
+        std::fs::write(&path, "{ \"port\": 8080,\n  \"host\" }\n").unwrap();
+
+        let err = Config::builder()
+            .add_source(File::from(path.as_path()).format(FileFormat::Json))
+            .build()
+            .unwrap_err();
+        let message = AppError::from(err).to_string();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(
+            message.contains("error-util-broken-settings.json"),
+            "{}",
+            message
+        );
+        assert!(message.contains("line 2"), "{}", message);
+    }
+}
@@ -0,0 +1,29 @@
+use hmac::digest::MacError;
+
+use super::AppError;
+
+impl From<MacError> for AppError {
+    fn from(_: MacError) -> Self {
+        // deliberately generic: say nothing about how the signature differed
+        AppError::custom(401, "SIGNATURE_INVALID", "invalid signature")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    use super::*;
+
+    #[test]
+    fn mismatch_is_a_401_with_signature_invalid() {
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"webhook secret").unwrap();
+        mac.update(b"{\"event\":\"paid\"}");
+        let err = AppError::from(mac.verify_slice(&[0; 32]).unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::UNAUTHORIZED);
+        assert_eq!(err.code(), "SIGNATURE_INVALID");
+    }
+}
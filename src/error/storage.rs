@@ -0,0 +1,69 @@
+use actix_web::http::StatusCode;
+use object_store::Error as ObjectStoreError;
+
+use super::AppError;
+
+pub(super) fn status_code(err: &ObjectStoreError) -> StatusCode {
+    match err {
+        ObjectStoreError::NotFound { .. } => StatusCode::NOT_FOUND,
+        ObjectStoreError::PermissionDenied { .. } | ObjectStoreError::Unauthenticated { .. } => {
+            StatusCode::FORBIDDEN
+        }
+        // throttling only surfaces once the client's own retries run out,
+        // and only in the upstream response text
+        _ if is_throttled(err) => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::BAD_GATEWAY,
+    }
+}
+
+fn is_throttled(err: &ObjectStoreError) -> bool {
+    let message = err.to_string();
+    ["SlowDown", "Too Many Requests", "Throttl"]
+        .iter()
+        .any(|marker| message.contains(marker))
+}
+
+impl From<ObjectStoreError> for AppError {
+    fn from(err: ObjectStoreError) -> Self {
+        AppError::ObjectStore(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::ResponseError;
+
+    use super::*;
+
+    fn source(message: &str) -> Box<dyn std::error::Error + Send + Sync> {
+        message.into()
+    }
+
+    #[test]
+    fn not_found_is_a_404() {
+        let err = AppError::from(ObjectStoreError::NotFound {
+            path: "invoices/42.pdf".into(),
+            source: source("404 Not Found"),
+        });
+        assert_eq!(err.status_code(), StatusCode::NOT_FOUND);
+        assert!(err.to_string().starts_with("object store error: "));
+    }
+
+    #[test]
+    fn access_denied_is_a_403() {
+        let err = AppError::from(ObjectStoreError::PermissionDenied {
+            path: "invoices/42.pdf".into(),
+            source: source("AccessDenied"),
+        });
+        assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn throttling_is_a_503() {
+        let err = AppError::from(ObjectStoreError::Generic {
+            store: "S3",
+            source: source("503 SlowDown: please reduce your request rate"),
+        });
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,56 @@
+use std::error::Error as _;
+
+use tera::{Error as TeraError, ErrorKind};
+
+use super::AppError;
+
+impl From<TeraError> for AppError {
+    fn from(err: TeraError) -> Self {
+        // both are our bug, but a missing template is a broken deploy while a
+        // render failure is usually bad data, and they alert differently
+        let code = match err.kind {
+            ErrorKind::TemplateNotFound(_) => "TEMPLATE_NOT_FOUND",
+            _ => "TEMPLATE_RENDER",
+        };
+
+        // tera keeps the actual reason in the source chain
+        let mut message = format!("template error: {}", err);
+        let mut source = err.source();
+        while let Some(cause) = source {
+            message.push_str(&format!(": {}", cause));
+            source = cause.source();
+        }
+        AppError::custom(500, code, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use tera::{Context, Tera};
+
+    use super::*;
+
+    #[test]
+    fn missing_template_is_template_not_found() {
+        let err = AppError::from(
+            Tera::default()
+                .render("missing.html", &Context::new())
+                .unwrap_err(),
+        );
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(err.code(), "TEMPLATE_NOT_FOUND");
+    }
+
+    #[test]
+    fn render_failure_is_template_render() {
+        let mut tera = Tera::default();
+        tera.add_raw_template("greeting.html", "hello {{ user.name }}")
+            .unwrap();
+        let err = AppError::from(tera.render("greeting.html", &Context::new()).unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(err.code(), "TEMPLATE_RENDER");
+        assert!(err.to_string().contains("user.name"));
+    }
+}
@@ -0,0 +1,140 @@
+use actix_web::ResponseError;
+use tonic::Status;
+
+use super::{AppError, AppResult, FieldError, HttpError};
+
+/// Terse assertions for error-path tests, e.g.
+/// `do_thing().await.assert_err_status(409);`.
+pub trait AppResultAssertExt {
+    fn assert_err_status(self, status: u16) -> AppError;
+    fn assert_err_code(self, code: &str) -> AppError;
+}
+
+impl<T: std::fmt::Debug> AppResultAssertExt for AppResult<T> {
+    #[track_caller]
+    fn assert_err_status(self, status: u16) -> AppError {
+        let err = self.expect_err("expected an error");
+        assert_eq!(
+            err.status_code().as_u16(),
+            status,
+            "unexpected status for error: {}",
+            err
+        );
+        err
+    }
+
+    #[track_caller]
+    fn assert_err_code(self, code: &str) -> AppError {
+        let err = self.expect_err("expected an error");
+        assert_eq!(err.code(), code, "unexpected code for error: {}", err);
+        err
+    }
+}
+
+/// One representative error per variant family, for snapshotting.
+pub fn representative_errors() -> Vec<(&'static str, AppError)> {
+    vec![
+        ("unauthorized", AppError::Unauthorized),
+        ("bad_request", AppError::BadRequest("missing name".into())),
+        ("not_found", AppError::NotFound("user 42".into())),
+        (
+            "validation",
+            AppError::Validation(vec![FieldError {
+                field: "email".into(),
+                message: "invalid".into(),
+            }]),
+        ),
+        (
+            "conflict",
+            AppError::custom(409, "DUPLICATE", "already exists"),
+        ),
+        (
+            "server_error",
+            AppError::ServerError(HttpError {
+                status: 500,
+                message: "boom".into(),
+            }),
+        ),
+        (
+            "grpc_status",
+            AppError::GrpcStatusError(Status::not_found("gone")),
+        ),
+        ("degraded", AppError::degraded(vec!["mongo".into()])),
+        (
+            "rate_limited",
+            AppError::RateLimited(std::time::Duration::from_secs(3)),
+        ),
+        (
+            "not_implemented",
+            AppError::not_implemented(Some("export".into())),
+        ),
+    ]
+}
+
+impl AppError {
+    /// The HTTP, gRPC and GraphQL representations as one stable text block,
+    /// so any mapping change shows up as a snapshot diff.
+    pub fn snapshot(&self) -> String {
+        let grpc = self.convert_app_error_to_grpc_status();
+        let graphql = self.convert_app_error_to_graphql_error();
+        format!(
+            "http: {} {}\ngrpc: {:?} {}\ngraphql: {} {}\n",
+            self.status_code().as_u16(),
+            serde_json::to_string(&self.envelope()).unwrap_or_default(),
+            grpc.code(),
+            grpc.message(),
+            graphql.message,
+            serde_json::to_string(&graphql.extensions).unwrap_or_default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(clippy::result_large_err)]
+    fn find(id: u32) -> AppResult<u32> {
+        Err(AppError::NotFound(format!("user {}", id)))
+    }
+
+    const SNAPSHOTS: &str = "src/error/snapshots/representations.txt";
+
+    fn snapshots() -> String {
+        representative_errors()
+            .into_iter()
+            .map(|(name, err)| format!("== {} ==\n{}", name, err.snapshot()))
+            .collect()
+    }
+
+    /// Any change to how an error is represented shows up here; review the
+    /// diff and rerun with `UPDATE_SNAPSHOTS=1` to accept it.
+    #[test]
+    fn representations_match_the_snapshots() {
+        let actual = snapshots();
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(SNAPSHOTS, &actual).unwrap();
+            return;
+        }
+        assert_eq!(actual, include_str!("snapshots/representations.txt"));
+    }
+
+    #[test]
+    fn matching_assertions_return_the_error() {
+        let err = find(42).assert_err_status(404);
+        assert_eq!(err.to_string(), "not found: user 42");
+        find(42).assert_err_code("NOT_FOUND");
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected status for error")]
+    fn mismatching_status_panics() {
+        find(42).assert_err_status(409);
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected code for error")]
+    fn mismatching_code_panics() {
+        find(42).assert_err_code("CONFLICT");
+    }
+}
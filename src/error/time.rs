@@ -0,0 +1,42 @@
+use time::error::{ComponentRange, Parse as ParseError};
+
+use super::AppError;
+
+impl From<ComponentRange> for AppError {
+    fn from(err: ComponentRange) -> Self {
+        AppError::OutOfRange(format!("invalid date or time: {}", err))
+    }
+}
+
+impl From<ParseError> for AppError {
+    fn from(err: ParseError) -> Self {
+        AppError::BadRequest(format!("malformed date or time: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use time::format_description::well_known::Rfc3339;
+    use time::{Date, Month, OffsetDateTime};
+    use tonic::Code;
+
+    use super::*;
+
+    #[test]
+    fn range_error_is_a_416() {
+        let err = AppError::from(Date::from_calendar_date(2024, Month::February, 30).unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            err.convert_app_error_to_grpc_status().code(),
+            Code::OutOfRange
+        );
+    }
+
+    #[test]
+    fn parse_error_is_a_bad_request() {
+        let err = AppError::from(OffsetDateTime::parse("yesterday", &Rfc3339).unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,54 @@
+use actix_web::http::StatusCode;
+use rustls::Error as TlsError;
+
+use super::AppError;
+
+/// 502 when the peer's certificate or handshake was at fault, 500 when our
+/// own keys, roots or environment are.
+pub(super) fn status_code(err: &TlsError) -> StatusCode {
+    match err {
+        TlsError::InvalidCertificate(_)
+        | TlsError::NoCertificatesPresented
+        | TlsError::UnsupportedNameType
+        | TlsError::PeerIncompatible(_)
+        | TlsError::PeerMisbehaved(_)
+        | TlsError::PeerSentOversizedRecord
+        | TlsError::AlertReceived(_)
+        | TlsError::InappropriateMessage { .. }
+        | TlsError::InappropriateHandshakeMessage { .. }
+        | TlsError::InvalidMessage(_)
+        | TlsError::DecryptError
+        | TlsError::NoApplicationProtocol => StatusCode::BAD_GATEWAY,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl From<TlsError> for AppError {
+    fn from(err: TlsError) -> Self {
+        AppError::Tls(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use rustls::{CertificateError, InconsistentKeys};
+
+    use super::*;
+
+    #[test]
+    fn bad_peer_certificate_is_a_bad_gateway() {
+        let err = AppError::from(TlsError::InvalidCertificate(
+            CertificateError::UnknownIssuer,
+        ));
+        assert_eq!(err.status_code(), StatusCode::BAD_GATEWAY);
+        assert!(err.to_string().starts_with("tls error: "));
+    }
+
+    #[test]
+    fn missing_local_key_is_an_internal_error() {
+        let err = AppError::from(TlsError::InconsistentKeys(InconsistentKeys::Unknown));
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,37 @@
+use tower::load_shed::error::Overloaded;
+use tower::timeout::error::Elapsed;
+
+use super::AppError;
+
+impl From<Elapsed> for AppError {
+    fn from(_: Elapsed) -> Self {
+        AppError::Timeout("request timed out".into())
+    }
+}
+
+impl From<Overloaded> for AppError {
+    fn from(_: Overloaded) -> Self {
+        AppError::ServiceUnavailable("service overloaded, retry shortly".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+
+    use super::*;
+
+    #[test]
+    fn elapsed_is_a_504() {
+        let err = AppError::from(Elapsed::new());
+        assert_eq!(err.status_code(), StatusCode::GATEWAY_TIMEOUT);
+    }
+
+    #[test]
+    fn overloaded_is_a_503_with_a_retry_hint() {
+        let response = ResponseError::error_response(&AppError::from(Overloaded::new()));
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(response.headers().contains_key("Retry-After"));
+    }
+}
@@ -0,0 +1,41 @@
+use headers::Error as HeadersError;
+
+use super::AppError;
+
+/// `headers::Error` does not say which header failed to decode; callers
+/// that know it should prefer [`AppError::invalid_client_header`].
+impl From<HeadersError> for AppError {
+    fn from(err: HeadersError) -> Self {
+        AppError::BadRequest(format!("malformed header: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use headers::{ContentLength, Header, HeaderValue};
+
+    use super::*;
+
+    fn malformed_content_length() -> HeadersError {
+        let value = HeaderValue::from_static("twelve");
+        ContentLength::decode(&mut std::iter::once(&value)).unwrap_err()
+    }
+
+    #[test]
+    fn malformed_typed_header_is_a_bad_request() {
+        let err = AppError::from(malformed_content_length());
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn invalid_client_header_names_it() {
+        let named = AppError::invalid_client_header(
+            ContentLength::name().as_str(),
+            malformed_content_length(),
+        );
+        assert_eq!(named.status_code(), StatusCode::BAD_REQUEST);
+        assert!(named.to_string().contains("content-length"));
+    }
+}
@@ -0,0 +1,58 @@
+use validator::{ValidationError, ValidationErrors};
+
+use super::{AppError, FieldError};
+
+fn field_error(field: &str, err: &ValidationError) -> FieldError {
+    FieldError {
+        field: field.to_string(),
+        message: err
+            .message
+            .as_deref()
+            .unwrap_or(err.code.as_ref())
+            .to_string(),
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(err: ValidationErrors) -> Self {
+        let mut fields: Vec<_> = err.field_errors().into_iter().collect();
+        fields.sort_by_key(|(field, _)| *field);
+
+        AppError::Validation(
+            fields
+                .into_iter()
+                .flat_map(|(field, errors)| errors.iter().map(move |err| field_error(field, err)))
+                .collect(),
+        )
+    }
+}
+
+/// A standalone error has no field name, so its code stands in for one.
+impl From<ValidationError> for AppError {
+    fn from(err: ValidationError) -> Self {
+        AppError::Validation(vec![field_error(&err.code, &err)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::body::MessageBody;
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+
+    use super::*;
+
+    #[test]
+    fn single_error_is_a_422_with_one_field() {
+        let err = AppError::from(ValidationError::new("email").with_message("not an email".into()));
+        let response = ResponseError::error_response(&err);
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body: serde_json::Value =
+            serde_json::from_slice(&response.into_body().try_into_bytes().unwrap()).unwrap();
+        assert_eq!(
+            body["errors"],
+            serde_json::json!([{ "field": "email", "message": "not an email" }])
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use semver::Error as SemverError;
+
+use super::AppError;
+
+impl AppError {
+    /// For versions negotiated through a request header; names the value
+    /// the client sent, which `semver`'s own message leaves out.
+    pub fn invalid_version(version: &str, err: SemverError) -> Self {
+        AppError::BadRequest(format!("invalid version {:?}: {}", version, err))
+    }
+}
+
+impl From<SemverError> for AppError {
+    fn from(err: SemverError) -> Self {
+        AppError::BadRequest(format!("invalid version: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use semver::{Version, VersionReq};
+
+    use super::*;
+
+    #[test]
+    fn invalid_version_is_a_bad_request() {
+        let err = AppError::from(Version::parse("1.x").unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn invalid_version_names_the_value_sent() {
+        let header = ">=banana";
+        let err = AppError::invalid_version(header, VersionReq::parse(header).unwrap_err());
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert!(err.to_string().contains("\">=banana\""));
+    }
+}
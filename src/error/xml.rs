@@ -0,0 +1,73 @@
+use quick_xml::DeError as QuickXmlError;
+use xml::common::Position;
+use xml::reader::Error as XmlReaderError;
+
+use super::AppError;
+
+impl From<QuickXmlError> for AppError {
+    fn from(err: QuickXmlError) -> Self {
+        match err {
+            // the document itself could not be parsed
+            QuickXmlError::InvalidXml(_) | QuickXmlError::UnexpectedEof => {
+                AppError::BadRequest(format!("malformed xml: {}", err))
+            }
+            // the document parsed but does not match the expected shape
+            _ => AppError::UnprocessableEntity(format!("invalid xml: {}", err)),
+        }
+    }
+}
+
+impl From<XmlReaderError> for AppError {
+    fn from(err: XmlReaderError) -> Self {
+        let position = err.position();
+        AppError::BadRequest(format!(
+            "malformed xml at line {}, column {}: {}",
+            position.row + 1,
+            position.column + 1,
+            err.msg()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+    use serde::Deserialize;
+    use xml::reader::EventReader;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Item {
+        count: u32,
+    }
+
+    #[test]
+    fn malformed_xml_is_a_bad_request() {
+        let err: AppError = quick_xml::de::from_str::<Item>("<item><count>1</item>")
+            .unwrap_err()
+            .into();
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn well_formed_but_invalid_xml_is_unprocessable() {
+        let err: AppError = quick_xml::de::from_str::<Item>("<item><count>many</count></item>")
+            .unwrap_err()
+            .into();
+        assert_eq!(err.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn reader_errors_carry_the_position() {
+        let err = EventReader::from_str("<a>\n  <b></a>")
+            .into_iter()
+            .find_map(Result::err)
+            .unwrap();
+        let err = AppError::from(err);
+        assert_eq!(err.status_code(), StatusCode::BAD_REQUEST);
+        assert!(err.to_string().contains("line 2"), "{}", err);
+    }
+}
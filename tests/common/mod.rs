@@ -0,0 +1,69 @@
+// each test binary uses a different subset
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use error_util::error::{AppError, ObservabilityConfig};
+use tracing_subscriber::util::SubscriberInitExt;
+
+static SETTINGS: Mutex<()> = Mutex::new(());
+
+/// Serializes the tests that change process-wide settings, putting every
+/// setting back to its default first; hold the guard for the whole test.
+/// Help URLs, standard error rules and registered codes only accumulate, so
+/// each test registers its own.
+pub fn settings() -> MutexGuard<'static, ()> {
+    let guard = SETTINGS.lock().unwrap_or_else(PoisonError::into_inner);
+    AppError::init(ObservabilityConfig::default()).unwrap();
+    AppError::set_log_sampler(Box::new(|_| true));
+    AppError::set_client_detail_policy(Default::default());
+    AppError::set_retry_after_format(Default::default());
+    AppError::set_strict_codes(false);
+    AppError::set_problem_base_uri("");
+    AppError::set_stripped_headers(Vec::<String>::new());
+    AppError::set_pre_response_hook(Box::new(|_, _| {}));
+    guard
+}
+
+/// Collects what the crate logs while a closure runs on this thread.
+#[derive(Clone, Default)]
+pub struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Logs {
+    pub fn capture<T>(&self, f: impl FnOnce() -> T) -> T {
+        let logs = self.clone();
+        let _guard = tracing_subscriber::fmt()
+            .with_writer(move || logs.clone())
+            .with_ansi(false)
+            .finish()
+            .set_default();
+        f()
+    }
+
+    pub fn contents(&self) -> String {
+        let bytes = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
+/// Installs a global subscriber that discards everything, which
+/// [`AppError::init`](error_util::error::AppError::init) requires before it
+/// enables tracing. [`Logs::capture`] still overrides it on its thread.
+pub fn install_subscriber() {
+    let _ = tracing_subscriber::registry().try_init();
+}
+
+impl Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+//! Kept out of `settings.rs`: `init` must run before anything in the process
+//! installs a tracing subscriber.
+
+mod common;
+
+use actix_web::ResponseError;
+use error_util::error::{AppError, HttpError, ObservabilityConfig};
+
+use common::{install_subscriber, settings, Logs};
+
+fn server_error() -> AppError {
+    AppError::ServerError(HttpError {
+        status: 500,
+        message: "ledger write failed".into(),
+    })
+}
+
+fn config() -> ObservabilityConfig {
+    ObservabilityConfig {
+        tracing: cfg!(feature = "tracing"),
+        metrics: cfg!(feature = "metrics"),
+        sentry: cfg!(feature = "sentry"),
+        production_mode: true,
+        service_name: "billing".into(),
+    }
+}
+
+#[cfg(feature = "sentry")]
+#[allow(clippy::result_large_err)]
+fn init_with_a_sentry_client(config: ObservabilityConfig) -> Result<(), AppError> {
+    let mut result = Ok(());
+    sentry_core::test::with_captured_events(|| result = AppError::init(config));
+    result
+}
+
+#[cfg(not(feature = "sentry"))]
+#[allow(clippy::result_large_err)]
+fn init_with_a_sentry_client(config: ObservabilityConfig) -> Result<(), AppError> {
+    AppError::init(config)
+}
+
+#[test]
+fn init_configures_every_hook() {
+    let _settings = settings();
+    // the only test here that installs a subscriber, so none is set yet
+    if cfg!(feature = "tracing") {
+        assert!(init_with_a_sentry_client(config()).is_err());
+    }
+    install_subscriber();
+    if cfg!(feature = "sentry") {
+        assert!(AppError::init(config()).is_err());
+    }
+    init_with_a_sentry_client(config()).unwrap();
+
+    let err = server_error();
+
+    // production mode redacts the client-facing message only
+    assert_eq!(
+        serde_json::to_value(err.envelope()).unwrap()["message"],
+        "internal server error"
+    );
+    let log = err.to_log_json();
+    assert_eq!(log["context"]["service.name"], "billing");
+    assert!(log["message"]
+        .as_str()
+        .unwrap()
+        .contains("ledger write failed"));
+
+    let logs = Logs::default();
+    logs.capture(|| err.error_response());
+    if cfg!(feature = "tracing") {
+        let contents = logs.contents();
+        assert!(contents.contains("service.name=billing"), "{}", contents);
+        assert!(
+            contents.contains("error.kind=\"ServerError\""),
+            "{}",
+            contents
+        );
+    }
+
+    #[cfg(feature = "metrics")]
+    {
+        let recorder = metrics_util::debugging::DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, || err.error_response());
+        let counters = snapshotter.snapshot().into_vec();
+        let (key, ..) = &counters[0];
+        assert_eq!(key.key().name(), "app_errors_total");
+        assert!(key
+            .key()
+            .labels()
+            .any(|label| label.key() == "kind" && label.value() == "ServerError"));
+    }
+
+    #[cfg(feature = "sentry")]
+    {
+        let events = sentry_core::test::with_captured_events(|| {
+            err.error_response();
+        });
+        assert_eq!(events.len(), 1);
+    }
+}
+
+#[test]
+fn init_rejects_integrations_that_are_not_compiled_in() {
+    let _settings = settings();
+    let unavailable = [
+        (!cfg!(feature = "tracing")).then(|| ObservabilityConfig {
+            tracing: true,
+            ..Default::default()
+        }),
+        (!cfg!(feature = "metrics")).then(|| ObservabilityConfig {
+            metrics: true,
+            ..Default::default()
+        }),
+        (!cfg!(feature = "sentry")).then(|| ObservabilityConfig {
+            sentry: true,
+            ..Default::default()
+        }),
+    ];
+    for config in unavailable.into_iter().flatten() {
+        assert!(AppError::init(config).is_err());
+    }
+}
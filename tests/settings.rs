@@ -0,0 +1,377 @@
+mod common;
+
+use std::time::Duration;
+
+use actix_web::body::MessageBody;
+use actix_web::http::header::{HeaderName, HeaderValue, HttpDate};
+use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+use actix_web::{web, App, HttpResponse, ResponseError};
+#[cfg(feature = "tracing")]
+use error_util::error::ResultExt;
+use error_util::error::{
+    AppError, ClientDetailPolicy, FieldError, HttpError, ObservabilityConfig, RetryAfterFormat,
+    StripHeaders,
+};
+#[cfg(feature = "tracing")]
+use mongodb::error::Error as MongoError;
+
+use common::settings;
+#[cfg(feature = "tracing")]
+use common::{install_subscriber, Logs};
+
+fn causes(err: &AppError) -> serde_json::Value {
+    serde_json::to_value(err.envelope()).unwrap()["causes"].clone()
+}
+
+#[test]
+fn cause_tree_is_only_returned_in_development() {
+    let _settings = settings();
+    let err = AppError::ServerError(HttpError {
+        status: 500,
+        message: "ledger write failed".into(),
+    })
+    .normalize_upstream();
+
+    assert_eq!(
+        causes(&err),
+        serde_json::json!(["External Server error: api error: ledger write failed"])
+    );
+
+    AppError::init(ObservabilityConfig {
+        production_mode: true,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(causes(&err), serde_json::Value::Null);
+}
+
+#[test]
+fn generic_policy_hides_the_message_but_keeps_the_code() {
+    let _settings = settings();
+    let err = AppError::NotFound("user 123".into());
+    let body = serde_json::to_value(err.envelope()).unwrap();
+    assert_eq!(body["message"], "user 123");
+
+    AppError::set_client_detail_policy(ClientDetailPolicy::Generic);
+    let body = serde_json::to_value(err.envelope()).unwrap();
+    assert_eq!(body["message"], "not found");
+    assert_eq!(body["code"], "NOT_FOUND");
+}
+
+#[test]
+fn registered_help_url_appears_in_the_body() {
+    let _settings = settings();
+    let err = AppError::Validation(vec![FieldError {
+        field: "email".into(),
+        message: "invalid".into(),
+    }]);
+    let body = serde_json::to_value(err.envelope()).unwrap();
+    assert!(body.get("help_url").is_none());
+
+    AppError::set_help_url(err.code(), "https://docs.example.com/errors/validation");
+    let body = serde_json::to_value(err.envelope()).unwrap();
+    assert_eq!(
+        body["help_url"],
+        "https://docs.example.com/errors/validation"
+    );
+}
+
+#[test]
+fn type_uri_is_built_from_the_base_and_code() {
+    let _settings = settings();
+    let err = AppError::NotFound("order 42".into());
+    assert_eq!(err.problem_details().type_uri, "about:blank");
+
+    AppError::set_problem_base_uri("https://errors.example.com/");
+    assert_eq!(
+        err.problem_details().type_uri,
+        "https://errors.example.com/NOT_FOUND"
+    );
+}
+
+fn retry_after(err: &AppError) -> String {
+    let response = err.error_response();
+    response
+        .headers()
+        .get("Retry-After")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_owned()
+}
+
+#[test]
+fn retry_after_renders_as_seconds_or_an_http_date() {
+    let _settings = settings();
+    let rate_limited = AppError::RateLimited(Duration::from_millis(2500));
+    let unavailable = AppError::ServiceUnavailable("maintenance".into());
+
+    assert_eq!(retry_after(&rate_limited), "3");
+    assert_eq!(retry_after(&unavailable), "1");
+
+    AppError::set_retry_after_format(RetryAfterFormat::HttpDate);
+    for err in [&rate_limited, &unavailable] {
+        let value = retry_after(err);
+        assert!(value.ends_with(" GMT"), "{}", value);
+        assert!(value.parse::<HttpDate>().is_ok(), "{}", value);
+    }
+}
+
+#[test]
+fn matching_standard_error_becomes_a_404() {
+    let _settings = settings();
+    AppError::add_standard_error_rule("not found", 404, "USER_NOT_FOUND");
+
+    let err = AppError::StandardError("user 42 not found".into());
+    assert_eq!(err.status_code().as_u16(), 404);
+    assert_eq!(err.code(), "USER_NOT_FOUND");
+
+    let err = AppError::StandardError("disk full".into());
+    assert_eq!(err.status_code().as_u16(), 500);
+}
+
+#[test]
+fn unregistered_codes_surface_as_unknown_under_strict_mode() {
+    let _settings = settings();
+    let unregistered = AppError::custom(409, "LEDGER_LOCKED", "ledger is locked");
+    let registered = AppError::custom(409, "DUPLICATE_ORDER", "order already placed");
+    assert_eq!(unregistered.code(), "LEDGER_LOCKED");
+
+    AppError::register_codes(["DUPLICATE_ORDER"]);
+    AppError::set_strict_codes(true);
+    assert_eq!(unregistered.code(), "UNKNOWN");
+    let body = serde_json::to_value(unregistered.envelope()).unwrap();
+    assert_eq!(body["code"], "UNKNOWN");
+    assert_eq!(unregistered.kind(), "Custom");
+    assert_eq!(registered.code(), "DUPLICATE_ORDER");
+
+    AppError::set_strict_codes(false);
+    assert_eq!(unregistered.code(), "LEDGER_LOCKED");
+}
+
+// the settings lock is taken outside the runtime so it is never held
+// across an await
+
+#[test]
+fn hook_header_appears_on_the_response() {
+    let _settings = settings();
+    AppError::set_pre_response_hook(Box::new(|err, parts| {
+        if let Ok(code) = HeaderValue::from_str(&err.code()) {
+            parts
+                .headers
+                .insert(HeaderName::from_static("x-audit-code"), code);
+        }
+    }));
+
+    actix_web::rt::System::new().block_on(async {
+        let app = init_service(App::new().route(
+            "/orders/{id}",
+            web::get().to(|| async { Err::<String, _>(AppError::NotFound("order 42".into())) }),
+        ))
+        .await;
+        let response = call_service(&app, TestRequest::get().uri("/orders/42").to_request()).await;
+
+        assert_eq!(response.status().as_u16(), 404);
+        assert_eq!(response.headers().get("x-audit-code").unwrap(), "NOT_FOUND");
+        let body: serde_json::Value = read_body_json(response).await;
+        assert_eq!(body["message"], "order 42");
+    });
+}
+
+#[test]
+fn configured_internal_header_is_absent_from_the_error_response() {
+    let _settings = settings();
+    AppError::set_stripped_headers(["x-internal-token"]);
+    // stands in for a proxy copying upstream headers onto the response
+    AppError::set_pre_response_hook(Box::new(|_, parts| {
+        parts.headers.insert(
+            HeaderName::from_static("x-internal-token"),
+            HeaderValue::from_static("secret"),
+        );
+        parts.headers.insert(
+            HeaderName::from_static("x-request-region"),
+            HeaderValue::from_static("eu-west-1"),
+        );
+    }));
+
+    actix_web::rt::System::new().block_on(async {
+        let app = init_service(App::new().route(
+            "/orders/{id}",
+            web::get().to(|| async { Err::<String, _>(AppError::NotFound("order 42".into())) }),
+        ))
+        .await;
+        let response = call_service(&app, TestRequest::get().uri("/orders/42").to_request()).await;
+
+        assert_eq!(response.status().as_u16(), 404);
+        assert!(!response.headers().contains_key("x-internal-token"));
+        assert!(response.headers().contains_key("x-request-region"));
+    });
+}
+
+#[test]
+fn middleware_strips_headers_a_handler_sets_itself() {
+    let _settings = settings();
+    AppError::set_stripped_headers(["x-internal-token"]);
+
+    actix_web::rt::System::new().block_on(async {
+        let app = init_service(
+            App::new()
+                .route(
+                    "/proxy",
+                    web::get().to(|| async {
+                        HttpResponse::BadGateway()
+                            .insert_header(("x-internal-token", "secret"))
+                            .insert_header(("x-request-region", "eu-west-1"))
+                            .finish()
+                    }),
+                )
+                .route(
+                    "/health",
+                    web::get().to(|| async {
+                        HttpResponse::Ok()
+                            .insert_header(("x-internal-token", "secret"))
+                            .finish()
+                    }),
+                )
+                .wrap(StripHeaders),
+        )
+        .await;
+
+        let response = call_service(&app, TestRequest::get().uri("/proxy").to_request()).await;
+        assert_eq!(response.status().as_u16(), 502);
+        assert!(!response.headers().contains_key("x-internal-token"));
+        assert!(response.headers().contains_key("x-request-region"));
+
+        // successful responses are left alone
+        let response = call_service(&app, TestRequest::get().uri("/health").to_request()).await;
+        assert!(response.headers().contains_key("x-internal-token"));
+    });
+}
+
+#[cfg(feature = "tracing")]
+fn ids(err: &AppError, logs: &Logs) -> (String, String) {
+    let response = logs.capture(|| err.error_response());
+    let header = response
+        .headers()
+        .get("X-Error-Id")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body: serde_json::Value =
+        serde_json::from_slice(&response.into_body().try_into_bytes().unwrap()).unwrap();
+    (header, body["id"].as_str().unwrap().to_string())
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn the_same_id_is_in_the_body_header_and_log() {
+    let _settings = settings();
+    install_subscriber();
+    AppError::init(ObservabilityConfig {
+        tracing: true,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let logs = Logs::default();
+    let (header, body) = ids(&AppError::NotFound("order 42".into()), &logs);
+    assert_eq!(header, body);
+    assert!(
+        logs.contents()
+            .contains(&format!("error.id=\"{}\"", header)),
+        "{}",
+        logs.contents()
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn a_pinned_id_is_stable_across_responses() {
+    let _settings = settings();
+    let err = AppError::NotFound("order 42".into()).with_error_id();
+    let logs = Logs::default();
+    let first = ids(&err, &logs);
+    assert_eq!(first, ids(&err, &logs));
+    assert_eq!(first.0, err.error_id().unwrap());
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn an_unpinned_id_is_stable_across_responses() {
+    let _settings = settings();
+    let err = AppError::ServerError(HttpError {
+        status: 500,
+        message: "ledger unreachable".into(),
+    })
+    .with_context("order_id", 42);
+    let logs = Logs::default();
+    let first = ids(&err, &logs);
+    assert_eq!(first, ids(&err, &logs));
+}
+
+#[test]
+fn not_modified_goes_through_the_same_path() {
+    let _settings = settings();
+    let err = AppError::not_modified().with_error_id();
+    let response = err.error_response();
+    assert_eq!(response.status().as_u16(), 304);
+    assert_eq!(
+        response.headers().get("X-Error-Id").unwrap(),
+        err.error_id().unwrap()
+    );
+    assert!(response.into_body().try_into_bytes().unwrap().is_empty());
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn sampler_suppresses_not_found_but_permits_mongo() {
+    let _settings = settings();
+    install_subscriber();
+    AppError::init(ObservabilityConfig {
+        tracing: true,
+        ..Default::default()
+    })
+    .unwrap();
+    AppError::set_log_sampler(Box::new(|err| {
+        !matches!(err.unwrapped(), AppError::NotFound(_))
+    }));
+
+    let logs = Logs::default();
+    logs.capture(|| AppError::NotFound("order 42".into()).error_response());
+    logs.capture(|| {
+        AppError::NotFound("order 43".into())
+            .with_context("tenant", "acme")
+            .error_response()
+    });
+    assert!(logs.contents().is_empty(), "{}", logs.contents());
+
+    logs.capture(|| AppError::Mongo(MongoError::custom("primary stepped down")).error_response());
+    assert!(
+        logs.contents().contains("error.kind=\"Mongo\""),
+        "{}",
+        logs.contents()
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+#[allow(clippy::result_large_err)]
+fn or_log_emits_the_event_and_returns_the_result_unchanged() {
+    let _settings = settings();
+    install_subscriber();
+    AppError::init(ObservabilityConfig {
+        tracing: true,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let logs = Logs::default();
+    let result: Result<u32, AppError> =
+        logs.capture(|| Err(AppError::NotFound("order 42".into())).or_log());
+    assert!(matches!(result, Err(AppError::NotFound(ref id)) if id == "order 42"));
+    assert!(logs.contents().contains("order 42"), "{}", logs.contents());
+
+    let ok: Result<u32, AppError> = logs.capture(|| Ok(7).or_log());
+    assert_eq!(ok.unwrap(), 7);
+}